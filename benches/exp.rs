@@ -0,0 +1,33 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use zkp::{params, Group, RistrettoGroup};
+
+fn bench_group<G: Group>(c: &mut Criterion, name: &str, group: &G, alpha: &G::Element, beta: &G::Element) {
+    let mut bench = c.benchmark_group(format!("exp/{name}"));
+    let table = group.precompute(alpha);
+    let x = group.random_scalar();
+    let y = group.random_scalar();
+
+    bench.bench_function("variable-base", |b| b.iter(|| group.exp(alpha, &x)));
+    bench.bench_function("fixed-base", |b| b.iter(|| group.exp_fixed(&table, &x)));
+    bench.bench_function("two-exps", |b| b.iter(|| group.op(&group.exp(alpha, &x), &group.exp(beta, &y))));
+    bench.bench_function("exp2", |b| b.iter(|| group.exp2(alpha, &table, &x, beta, &y)));
+    bench.bench_function("precompute", |b| b.iter(|| group.precompute(alpha)));
+    bench.finish();
+}
+
+fn exp(c: &mut Criterion) {
+    for set in [params::RFC5114_2048_256, params::FFDHE2048] {
+        bench_group(c, set.name, &set.group().unwrap(), &set.alpha(), &set.beta().unwrap());
+    }
+
+    let (alpha, beta) = RistrettoGroup::generators();
+    bench_group(c, "ristretto255", &RistrettoGroup::new(), &alpha, &beta);
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = exp
+}
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use zkp::{params, Commitment, Group, Proof, RistrettoGroup, Statement, ZKP};
+
+const BATCH_SIZES: [usize; 3] = [1, 16, 64];
+
+fn proofs<G: Group>(zkp: &ZKP<G>, n: usize) -> Vec<(Statement<G>, Commitment<G>, Proof<G>)> {
+    (0..n)
+        .map(|_| {
+            let x = zkp.group.random_scalar();
+            let k = zkp.group.random_scalar();
+            let c = zkp.group.random_scalar();
+            let s = zkp.solve(&k, &c, &x);
+            (zkp.statement(&x), zkp.commitment(&k), Proof { c, s })
+        })
+        .collect()
+}
+
+fn bench_group<G: Group>(c: &mut Criterion, name: &str, zkp: &ZKP<G>) {
+    let mut bench = c.benchmark_group(format!("verify/{name}"));
+    for n in BATCH_SIZES {
+        let proofs = proofs(zkp, n);
+        bench.bench_with_input(BenchmarkId::new("per-proof", n), &proofs, |b, proofs| {
+            b.iter(|| assert!(proofs.iter().all(|(statement, commitment, proof)| zkp.verify_proof(statement, commitment, proof))))
+        });
+        bench.bench_with_input(BenchmarkId::new("batch", n), &proofs, |b, proofs| {
+            b.iter(|| assert_eq!(zkp.verify_batch(proofs), Ok(())))
+        });
+    }
+    bench.finish();
+}
+
+fn verify(c: &mut Criterion) {
+    let zkp = params::RFC5114_2048_256.zkp().expect("Invalid parameter set");
+    bench_group(c, "rfc5114-2048-256", &zkp);
+
+    let (alpha, beta) = RistrettoGroup::generators();
+    bench_group(c, "ristretto255", &ZKP::new(RistrettoGroup::new(), alpha, beta));
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = verify
+}
+criterion_main!(benches);
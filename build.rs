@@ -1,7 +1,9 @@
 fn main() {
+    // the bindings are generated into OUT_DIR and only with the grpc feature,
+    // the rest of the crate builds without protoc
+    #[cfg(feature = "grpc")]
     tonic_build::configure()
         .build_server(true)
-        .out_dir("src/")
         .compile_protos(&["proto/zkp_auth.proto"], &["proto/"])
         .expect("Protobuf build error")
-}
\ No newline at end of file
+}
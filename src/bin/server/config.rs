@@ -0,0 +1,336 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::Parser;
+use serde::Deserialize;
+use tracing_subscriber::EnvFilter;
+use zkp::ParameterSet;
+
+use crate::limit::{LockoutPolicy, Rate, DEFAULT_LOCKOUT, DEFAULT_PEER_RATE, DEFAULT_USER_RATE};
+use crate::{DEFAULT_CHALLENGE_TTL, DEFAULT_SESSION_TTL};
+
+const DEFAULT_LISTEN: &str = "127.0.0.1:50051";
+
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+// Command line of the server. Every setting can also be given in the environment or in the
+// config file, flags win over the environment which wins over the file.
+#[derive(Debug, Default, Parser)]
+#[command(version, about = "ZKP authentication server")]
+pub struct Args {
+    #[arg(short, long, env = "ZKP_CONFIG", help = "TOML file with any of the settings below, in snake_case")]
+    pub config: Option<PathBuf>,
+
+    #[arg(long, env = "ZKP_LISTEN", help = "address to listen on [default: 127.0.0.1:50051]")]
+    pub listen: Option<String>,
+
+    #[arg(long, env = "ZKP_GROUP", help = "parameter set or `ristretto255`, it must match the one used by the client")]
+    pub group: Option<String>,
+
+    #[arg(long, env = "ZKP_DB", help = "SQLite database, everything is kept in memory without it")]
+    pub db: Option<PathBuf>,
+
+    #[arg(long, env = "ZKP_CHALLENGE_TTL_SECS", help = "seconds a challenge can be answered [default: 120]")]
+    pub challenge_ttl_secs: Option<u64>,
+
+    #[arg(long, env = "ZKP_SESSION_TTL_SECS", help = "seconds a session lasts after login or refresh [default: 3600]")]
+    pub session_ttl_secs: Option<u64>,
+
+    #[arg(long, env = "ZKP_TOKEN_KEY", help = "PEM Ed25519 private key, logins return tokens signed with it")]
+    pub token_key: Option<PathBuf>,
+
+    #[arg(long, env = "ZKP_ADMIN_TOKEN", hide_env_values = true, help = "enables deleting accounts and revoking sessions with the admin_token field")]
+    pub admin_token: Option<String>,
+
+    #[arg(long, env = "ZKP_TLS_CERT", help = "PEM certificate chain, the server speaks TLS when it and tls_key are set")]
+    pub tls_cert: Option<PathBuf>,
+
+    #[arg(long, env = "ZKP_TLS_KEY", help = "PEM private key of the certificate")]
+    pub tls_key: Option<PathBuf>,
+
+    #[arg(long, env = "ZKP_TLS_CLIENT_CA", help = "PEM CA certificate, clients must present a certificate it signed (mutual TLS)")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    #[arg(long, env = "ZKP_USER_RATE_PER_MIN", help = "requests a minute per user name once the burst is used up, 0 for no limit [default: 30]")]
+    pub user_rate_per_min: Option<u32>,
+
+    #[arg(long, env = "ZKP_USER_BURST", help = "requests per user name at once [default: 10]")]
+    pub user_burst: Option<u32>,
+
+    #[arg(long, env = "ZKP_PEER_RATE_PER_MIN", help = "requests a minute per remote address once the burst is used up, 0 for no limit [default: 300]")]
+    pub peer_rate_per_min: Option<u32>,
+
+    #[arg(long, env = "ZKP_PEER_BURST", help = "requests per remote address at once [default: 60]")]
+    pub peer_burst: Option<u32>,
+
+    #[arg(long, env = "ZKP_LOCKOUT_THRESHOLD", help = "failed logins in a row that lock an account, 0 for no lockout [default: 5]")]
+    pub lockout_threshold: Option<u32>,
+
+    #[arg(long, env = "ZKP_LOCKOUT_SECS", help = "seconds of the first lock, every further failure doubles it [default: 30]")]
+    pub lockout_secs: Option<u64>,
+
+    #[arg(long, env = "ZKP_LOCKOUT_MAX_SECS", help = "seconds a lock lasts at most, failures are forgotten after as long without one [default: 3600]")]
+    pub lockout_max_secs: Option<u64>,
+
+    #[arg(long, env = "ZKP_LOG", help = "level or tracing filter directives, e.g. `debug` or `server=debug,tonic=info` [default: info]")]
+    pub log_level: Option<String>,
+}
+
+// Contents of the config file, the same settings as the flags
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct File {
+    pub listen: Option<String>,
+    pub group: Option<String>,
+    pub db: Option<PathBuf>,
+    pub challenge_ttl_secs: Option<u64>,
+    pub session_ttl_secs: Option<u64>,
+    pub token_key: Option<PathBuf>,
+    pub admin_token: Option<String>,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    pub tls_client_ca: Option<PathBuf>,
+    pub user_rate_per_min: Option<u32>,
+    pub user_burst: Option<u32>,
+    pub peer_rate_per_min: Option<u32>,
+    pub peer_burst: Option<u32>,
+    pub lockout_threshold: Option<u32>,
+    pub lockout_secs: Option<u64>,
+    pub lockout_max_secs: Option<u64>,
+    pub log_level: Option<String>,
+}
+
+// TLS identity of the server, and the CA of the client certificates for mutual TLS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tls {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    pub client_ca: Option<PathBuf>,
+}
+
+// Validated settings the server runs with
+#[derive(Debug)]
+pub struct Config {
+    pub listen: SocketAddr,
+    pub group: String,
+    pub db: Option<PathBuf>,
+    pub challenge_ttl: Duration,
+    pub session_ttl: Duration,
+    pub token_key: Option<PathBuf>,
+    pub admin_token: Option<String>,
+    pub tls: Option<Tls>,
+    // rate limits per user name and per remote address, and the lockout, off when None
+    pub user_rate: Option<Rate>,
+    pub peer_rate: Option<Rate>,
+    pub lockout: Option<LockoutPolicy>,
+    // tracing filter directives, already checked
+    pub log_level: String,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    // every problem found, one per entry
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "could not read {}: {err}", path.display()),
+            ConfigError::Parse(path, err) => write!(f, "invalid config file {}: {err}", path.display()),
+            ConfigError::Invalid(problems) => write!(f, "invalid configuration:\n  {}", problems.join("\n  ")),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl File {
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|err| ConfigError::Read(path.to_path_buf(), err))?;
+        toml::from_str(&text).map_err(|err| ConfigError::Parse(path.to_path_buf(), err))
+    }
+}
+
+impl Config {
+    // Reads the config file named by the arguments, if any, and resolves the settings
+    pub fn load(args: Args) -> Result<Self, ConfigError> {
+        let file = match &args.config {
+            Some(path) => File::read(path)?,
+            None => File::default(),
+        };
+        Self::resolve(args, file)
+    }
+
+    // Merges the arguments over the file and checks the result, reporting every problem at once
+    pub fn resolve(args: Args, file: File) -> Result<Self, ConfigError> {
+        let mut problems = Vec::new();
+
+        let listen = args.listen.or(file.listen).unwrap_or_else(|| DEFAULT_LISTEN.to_string());
+        let listen = listen
+            .parse()
+            .map_err(|err| problems.push(format!("listen: {listen:?} is not an address: {err}")))
+            .ok();
+
+        let group = args.group.or(file.group).unwrap_or_else(|| zkp::params::DEFAULT_PARAMETER_SET.name.to_string());
+        if group != "ristretto255" {
+            if let Err(err) = ParameterSet::by_name(&group) {
+                problems.push(format!("group: {err}"));
+            }
+        }
+
+        let mut ttl = |name: &str, secs: Option<u64>, default: u64| match secs.unwrap_or(default) {
+            0 => {
+                problems.push(format!("{name}: must be at least one second"));
+                Duration::ZERO
+            }
+            secs => Duration::from_secs(secs),
+        };
+        let challenge_ttl = ttl("challenge_ttl_secs", args.challenge_ttl_secs.or(file.challenge_ttl_secs), DEFAULT_CHALLENGE_TTL.as_secs());
+        let session_ttl = ttl("session_ttl_secs", args.session_ttl_secs.or(file.session_ttl_secs), DEFAULT_SESSION_TTL.as_secs());
+
+        let mut existing = |name: &str, path: Option<PathBuf>| {
+            if let Some(path) = &path {
+                if !path.is_file() {
+                    problems.push(format!("{name}: {} is not a file", path.display()));
+                }
+            }
+            path
+        };
+        let token_key = existing("token_key", args.token_key.or(file.token_key));
+        let tls_cert = existing("tls_cert", args.tls_cert.or(file.tls_cert));
+        let tls_key = existing("tls_key", args.tls_key.or(file.tls_key));
+        let client_ca = existing("tls_client_ca", args.tls_client_ca.or(file.tls_client_ca));
+        let tls = match (tls_cert, tls_key) {
+            (Some(cert), Some(key)) => Some(Tls { cert, key, client_ca }),
+            (None, None) if client_ca.is_none() => None,
+            (None, None) => {
+                problems.push("tls_client_ca needs tls_cert and tls_key".to_string());
+                None
+            }
+            _ => {
+                problems.push("tls_cert and tls_key must be given together".to_string());
+                None
+            }
+        };
+
+        let mut rate = |name: &str, per_min: Option<u32>, burst: Option<u32>, default: Rate| {
+            let rate = Rate { per_min: per_min.unwrap_or(default.per_min), burst: burst.unwrap_or(default.burst) };
+            if rate.per_min > 0 && rate.burst == 0 {
+                problems.push(format!("{name}_burst: must be at least 1"));
+            }
+            Some(rate).filter(|rate| rate.per_min > 0)
+        };
+        let user_rate = rate("user", args.user_rate_per_min.or(file.user_rate_per_min), args.user_burst.or(file.user_burst), DEFAULT_USER_RATE);
+        let peer_rate = rate("peer", args.peer_rate_per_min.or(file.peer_rate_per_min), args.peer_burst.or(file.peer_burst), DEFAULT_PEER_RATE);
+
+        let lockout = LockoutPolicy {
+            threshold: args.lockout_threshold.or(file.lockout_threshold).unwrap_or(DEFAULT_LOCKOUT.threshold),
+            base: args.lockout_secs.or(file.lockout_secs).map_or(DEFAULT_LOCKOUT.base, Duration::from_secs),
+            max: args.lockout_max_secs.or(file.lockout_max_secs).map_or(DEFAULT_LOCKOUT.max, Duration::from_secs),
+        };
+        let lockout = Some(lockout).filter(|lockout| lockout.threshold > 0);
+        if let Some(lockout) = &lockout {
+            if lockout.base.is_zero() {
+                problems.push("lockout_secs: must be at least one second".to_string());
+            }
+            if lockout.max < lockout.base {
+                problems.push("lockout_max_secs: must be at least lockout_secs".to_string());
+            }
+        }
+
+        let admin_token = args.admin_token.or(file.admin_token).filter(|token| !token.is_empty());
+
+        let log_level = args.log_level.or(file.log_level).unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+        if let Err(err) = EnvFilter::try_new(&log_level) {
+            problems.push(format!("log_level: {log_level:?}: {err}"));
+        }
+
+        match listen {
+            Some(listen) if problems.is_empty() => Ok(Config {
+                listen,
+                group,
+                db: args.db.or(file.db),
+                challenge_ttl,
+                session_ttl,
+                token_key,
+                admin_token,
+                tls,
+                user_rate,
+                peer_rate,
+                lockout,
+                log_level,
+            }),
+            _ => Err(ConfigError::Invalid(problems)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn file(text: &str) -> File {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = Config::resolve(Args::default(), File::default()).unwrap();
+        assert_eq!(config.listen, DEFAULT_LISTEN.parse().unwrap());
+        assert_eq!(config.group, zkp::params::DEFAULT_PARAMETER_SET.name);
+        assert_eq!(config.challenge_ttl, DEFAULT_CHALLENGE_TTL);
+        assert_eq!(config.db, None);
+        assert_eq!(config.tls, None);
+        assert_eq!(config.user_rate, Some(DEFAULT_USER_RATE));
+        assert_eq!(config.lockout, Some(DEFAULT_LOCKOUT));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let file = file("listen = \"0.0.0.0:1\"\ngroup = \"ffdhe2048\"\nsession_ttl_secs = 60\n");
+        let args = Args { listen: Some("127.0.0.1:2".to_string()), ..Args::default() };
+        let config = Config::resolve(args, file).unwrap();
+        assert_eq!(config.listen, "127.0.0.1:2".parse().unwrap());
+        assert_eq!(config.group, "ffdhe2048");
+        assert_eq!(config.session_ttl, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_limits() {
+        // a rate of 0 or a threshold of 0 turns the limit off
+        let limits = file("user_burst = 3\npeer_rate_per_min = 0\nlockout_threshold = 0\n");
+        let args = Args { user_rate_per_min: Some(6), ..Args::default() };
+        let config = Config::resolve(args, limits).unwrap();
+        assert_eq!(config.user_rate, Some(Rate { per_min: 6, burst: 3 }));
+        assert_eq!(config.peer_rate, None);
+        assert_eq!(config.lockout, None);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(toml::from_str::<File>("port = 1").is_err());
+
+        let invalid = file("listen = \"nowhere\"\ngroup = \"rfc0000\"\nchallenge_ttl_secs = 0\ntls_key = \"/nonexistent\"\nlog_level = \"[\"\n");
+        let ConfigError::Invalid(problems) = Config::resolve(Args::default(), invalid).unwrap_err() else {
+            panic!("expected validation errors");
+        };
+        // listen, group, challenge_ttl_secs, tls_key missing, tls_key alone and log_level
+        assert_eq!(problems.len(), 6, "{problems:?}");
+
+        // mutual TLS without TLS
+        let ConfigError::Invalid(problems) = Config::resolve(Args::default(), file("tls_client_ca = \"Cargo.toml\"")).unwrap_err() else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(problems, ["tls_client_ca needs tls_cert and tls_key"]);
+
+        let ConfigError::Invalid(problems) = Config::resolve(Args::default(), file("peer_burst = 0\nlockout_secs = 0\n")).unwrap_err() else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(problems, ["peer_burst: must be at least 1", "lockout_secs: must be at least one second"]);
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tonic::metadata::MetadataValue;
+use tonic::Status;
+
+// Requests per user name, overridden with user_rate_per_min and user_burst
+pub const DEFAULT_USER_RATE: Rate = Rate { per_min: 30, burst: 10 };
+
+// Requests per remote address, overridden with peer_rate_per_min and peer_burst
+pub const DEFAULT_PEER_RATE: Rate = Rate { per_min: 300, burst: 60 };
+
+// Failed verifications before an account is locked and how long for, overridden with
+// lockout_threshold, lockout_secs and lockout_max_secs
+pub const DEFAULT_LOCKOUT: LockoutPolicy = LockoutPolicy {
+    threshold: 5,
+    base: Duration::from_secs(30),
+    max: Duration::from_secs(3600),
+};
+
+// A key may make `burst` requests at once, and one more every 60 / `per_min` seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rate {
+    pub per_min: u32,
+    pub burst: u32,
+}
+
+// From `threshold` failed verifications in a row on, every further failure locks the account
+// twice as long as the previous one, starting at `base` and up to `max`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    pub threshold: u32,
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl LockoutPolicy {
+    // how long the `n`th lock after reaching the threshold lasts, counting from 0
+    fn backoff(&self, n: u32) -> Duration {
+        self.base.checked_mul(1 << n.min(31)).unwrap_or(self.max).min(self.max)
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+// Token buckets, one per key
+pub struct RateLimiter<K> {
+    rate: Rate,
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+impl<K: Hash + Eq> RateLimiter<K> {
+    pub fn new(rate: Rate) -> Self {
+        RateLimiter { rate, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    // Takes a token from the bucket of `key`, or returns how long until there is one
+    pub fn check(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key).or_insert(Bucket { tokens: self.rate.burst as f64, updated: now });
+        bucket.tokens = self.refilled(bucket, now);
+        bucket.updated = now.max(bucket.updated);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) * 60.0 / self.rate.per_min as f64))
+        }
+    }
+
+    // Forgets the full buckets, a new one is the same
+    pub fn sweep(&self, now: Instant) {
+        let burst = self.rate.burst as f64;
+        self.buckets.lock().unwrap().retain(|_, bucket| self.refilled(bucket, now) < burst);
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.rate.per_min as f64 / 60.0).min(self.rate.burst as f64)
+    }
+}
+
+struct Failures {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+// Failed verifications per user name
+pub struct Lockout {
+    policy: LockoutPolicy,
+    failures: Mutex<HashMap<String, Failures>>,
+}
+
+impl Lockout {
+    pub fn new(policy: LockoutPolicy) -> Self {
+        Lockout { policy, failures: Mutex::new(HashMap::new()) }
+    }
+
+    // how long the account of `user` stays locked, if it is
+    pub fn check(&self, user: &str, now: Instant) -> Result<(), Duration> {
+        match self.failures.lock().unwrap().get(user).and_then(|failures| failures.locked_until) {
+            Some(until) if until > now => Err(until - now),
+            _ => Ok(()),
+        }
+    }
+
+    // Counts a failed verification, returns how long the account is locked for if it now is
+    pub fn failed(&self, user: &str, now: Instant) -> Option<Duration> {
+        let mut failures = self.failures.lock().unwrap();
+        let failures = failures.entry(user.to_string()).or_insert(Failures { count: 0, last: now, locked_until: None });
+        failures.count = failures.count.saturating_add(1);
+        failures.last = now;
+        let n = failures.count.checked_sub(self.policy.threshold)?;
+        let lock = self.policy.backoff(n);
+        failures.locked_until = Some(now + lock);
+        Some(lock)
+    }
+
+    pub fn succeeded(&self, user: &str) {
+        self.failures.lock().unwrap().remove(user);
+    }
+
+    // Forgets the failures of users who have not failed for as long as the longest lock
+    pub fn sweep(&self, now: Instant) {
+        let max = self.policy.max;
+        self.failures.lock().unwrap().retain(|_, failures| now.saturating_duration_since(failures.last) < max);
+    }
+}
+
+// The limits of the server, each of them off when absent
+pub struct Limits {
+    user: Option<RateLimiter<String>>,
+    peer: Option<RateLimiter<IpAddr>>,
+    lockout: Option<Lockout>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits::new(Some(DEFAULT_USER_RATE), Some(DEFAULT_PEER_RATE), Some(DEFAULT_LOCKOUT))
+    }
+}
+
+impl Limits {
+    pub fn new(user: Option<Rate>, peer: Option<Rate>, lockout: Option<LockoutPolicy>) -> Self {
+        Limits { user: user.map(RateLimiter::new), peer: peer.map(RateLimiter::new), lockout: lockout.map(Lockout::new) }
+    }
+
+    // Takes a token from the buckets of the peer and of the user, in that order, and refuses
+    // users who are locked out. A locked out user does not use up their tokens.
+    pub fn check(&self, peer: Option<IpAddr>, user: Option<&str>) -> Result<(), Status> {
+        let now = Instant::now();
+        if let (Some(limiter), Some(peer)) = (&self.peer, peer) {
+            limiter.check(peer, now).map_err(|retry_after| exhausted("Too many requests from this address", retry_after))?;
+        }
+        if let Some(user) = user {
+            self.check_locked(user)?;
+            if let Some(limiter) = &self.user {
+                limiter.check(user.to_string(), now).map_err(|retry_after| exhausted("Too many requests for this user", retry_after))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Refuses users who are locked out
+    pub fn check_locked(&self, user: &str) -> Result<(), Status> {
+        match &self.lockout {
+            Some(lockout) => lockout.check(user, Instant::now()).map_err(|retry_after| exhausted("Account is locked after failed logins", retry_after)),
+            None => Ok(()),
+        }
+    }
+
+    // Counts a failed verification for `user`, returns how long the account is locked for if it now is
+    pub fn failed(&self, user: &str) -> Option<Duration> {
+        self.lockout.as_ref()?.failed(user, Instant::now())
+    }
+
+    pub fn succeeded(&self, user: &str) {
+        if let Some(lockout) = &self.lockout {
+            lockout.succeeded(user);
+        }
+    }
+
+    pub fn sweep(&self) {
+        let now = Instant::now();
+        self.user.iter().for_each(|limiter| limiter.sweep(now));
+        self.peer.iter().for_each(|limiter| limiter.sweep(now));
+        self.lockout.iter().for_each(|lockout| lockout.sweep(now));
+    }
+}
+
+// RESOURCE_EXHAUSTED with the whole seconds to wait in the retry-after metadata
+fn exhausted(message: &str, retry_after: Duration) -> Status {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let mut status = Status::resource_exhausted(format!("{message}, retry in {secs}s"));
+    status.metadata_mut().insert("retry-after", MetadataValue::from(secs));
+    status
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(Rate { per_min: 60, burst: 2 });
+        let start = Instant::now();
+        assert_eq!(limiter.check("alice", start), Ok(()));
+        assert_eq!(limiter.check("alice", start), Ok(()));
+        assert_eq!(limiter.check("alice", start), Err(Duration::from_secs(1)));
+        // other keys have their own bucket
+        assert_eq!(limiter.check("bob", start), Ok(()));
+
+        // one token a second, never more than the burst
+        let later = start + Duration::from_millis(1500);
+        assert_eq!(limiter.check("alice", later), Ok(()));
+        assert_eq!(limiter.check("alice", later), Err(Duration::from_millis(500)));
+        let much_later = start + Duration::from_secs(60);
+        assert_eq!(limiter.check("alice", much_later), Ok(()));
+        assert_eq!(limiter.check("alice", much_later), Ok(()));
+        assert!(limiter.check("alice", much_later).is_err());
+
+        limiter.sweep(much_later);
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+        limiter.sweep(much_later + Duration::from_secs(2));
+        assert!(limiter.buckets.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lockout() {
+        let lockout = Lockout::new(LockoutPolicy { threshold: 3, base: Duration::from_secs(10), max: Duration::from_secs(30) });
+        let start = Instant::now();
+        assert_eq!(lockout.failed("alice", start), None);
+        assert_eq!(lockout.failed("alice", start), None);
+        assert_eq!(lockout.check("alice", start), Ok(()));
+
+        // locked for 10s, then 20s, then never more than 30s
+        assert_eq!(lockout.failed("alice", start), Some(Duration::from_secs(10)));
+        assert_eq!(lockout.check("alice", start + Duration::from_secs(4)), Err(Duration::from_secs(6)));
+        assert_eq!(lockout.check("bob", start), Ok(()));
+        let unlocked = start + Duration::from_secs(10);
+        assert_eq!(lockout.check("alice", unlocked), Ok(()));
+        assert_eq!(lockout.failed("alice", unlocked), Some(Duration::from_secs(20)));
+        assert_eq!(lockout.failed("alice", unlocked), Some(Duration::from_secs(30)));
+        assert_eq!(lockout.failed("alice", unlocked), Some(Duration::from_secs(30)));
+
+        // a success starts the count over
+        lockout.succeeded("alice");
+        assert_eq!(lockout.check("alice", unlocked), Ok(()));
+        assert_eq!(lockout.failed("alice", unlocked), None);
+
+        // and so does not failing for as long as the longest lock
+        lockout.sweep(unlocked + Duration::from_secs(29));
+        assert_eq!(lockout.failures.lock().unwrap().len(), 1);
+        lockout.sweep(unlocked + Duration::from_secs(30));
+        assert!(lockout.failures.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_exhausted() {
+        let status = exhausted("Too many requests", Duration::from_millis(2100));
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(status.message(), "Too many requests, retry in 3s");
+        assert_eq!(status.metadata().get("retry-after").unwrap(), "3");
+    }
+}
@@ -0,0 +1,503 @@
+// Helpers return tonic::Status like the handlers do, boxing it would only add noise
+#![allow(clippy::result_large_err)]
+
+use std::error::Error;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use clap::Parser;
+use sha2::{Digest, Sha256};
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+use zkp::store::{self, PendingChallenge, Session, Store, StoreError, UserInfo};
+use zkp::token::{Claims, TokenSigner};
+use zkp::{delete_context, generate_random_string, login_context, revoke_context, rotate_context, Group, ParameterSet, Proof, RistrettoGroup, Statement, ZKP};
+mod config;
+mod limit;
+
+use config::{Args, Config};
+use limit::Limits;
+
+use zkp::zkp_auth::{auth_server::{Auth, AuthServer}, AuthenticationAnswerResponse, AuthenticationChallengeRequest, RegisterRequest, RegisterResponse};
+use zkp::zkp_auth::{AuthenticationAnswerRequest, AuthenticationChallengeResponse, LoginRequest, SaltRequest, SaltResponse};
+use zkp::zkp_auth::{LogoutRequest, LogoutResponse, RefreshSessionRequest, RefreshSessionResponse, ValidateSessionRequest, ValidateSessionResponse};
+use zkp::zkp_auth::{PublicKey, PublicKeysRequest, PublicKeysResponse, RotateCredentialsRequest, RotateCredentialsResponse};
+use zkp::zkp_auth::{DeleteAccountRequest, DeleteAccountResponse, RevokeSessionsRequest, RevokeSessionsResponse};
+
+// How far the timestamp of a non-interactive login may drift from the server clock
+const LOGIN_MAX_SKEW_SECS: u64 = 300;
+
+// How long a challenge can be answered, overridden with challenge_ttl_secs
+const DEFAULT_CHALLENGE_TTL: Duration = Duration::from_secs(120);
+
+// How long a session is valid after login or refresh, overridden with session_ttl_secs
+const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(3600);
+
+// Length of the random session ids, 32 alphanumeric characters are about 190 bits
+const SESSION_ID_LEN: usize = 32;
+
+pub struct AuthImpl<G: Group> {
+    pub zkp: ZKP<G>,
+    pub store: Arc<dyn Store>,
+    pub challenge_ttl: Duration,
+    pub session_ttl: Duration,
+    // signs a token for every login when set
+    pub token_signer: Option<TokenSigner>,
+    // lets operators delete accounts and revoke sessions without the user's secret
+    pub admin_token: Option<String>,
+    // rate limits and lockout, shared with the sweeper that forgets stale entries
+    pub limits: Arc<Limits>,
+}
+
+impl<G: Group> AuthImpl<G> {
+    pub fn new(zkp: ZKP<G>, store: Arc<dyn Store>) -> Self {
+        AuthImpl { zkp, store, challenge_ttl: DEFAULT_CHALLENGE_TTL, session_ttl: DEFAULT_SESSION_TTL, token_signer: None, admin_token: None, limits: Arc::default() }
+    }
+
+    // a new session for a user who just proved knowledge of their secret
+    fn logged_in(&self, user: &str, auth_id: Option<String>) -> Result<AuthenticationAnswerResponse, Status> {
+        let session = self.issue_session(user)?;
+        info!(user, interactive = auth_id.is_some(), "Logged in");
+        let token = self.sign_token(&session, auth_id)?.unwrap_or_default();
+        Ok(AuthenticationAnswerResponse { session_id: session.session_id, expires_at: session.expires_at, token })
+    }
+
+    // a token for the session that expires with it, when the server has a signing key
+    fn sign_token(&self, session: &Session, auth_id: Option<String>) -> Result<Option<String>, Status> {
+        let Some(signer) = &self.token_signer else { return Ok(None) };
+        let claims = Claims { sub: session.user.clone(), iat: session.created_at, exp: session.expires_at, auth_id };
+        let token = signer.sign(&claims).map_err(|err| Status::internal(format!("Could not sign token: {err}")))?;
+        Ok(Some(token))
+    }
+
+    fn issue_session(&self, user: &str) -> Result<Session, Status> {
+        let now = unix_now();
+        let session = Session {
+            session_id: generate_random_string(SESSION_ID_LEN),
+            user: user.to_string(),
+            created_at: now,
+            expires_at: now + self.session_ttl.as_secs(),
+        };
+        self.store.put_session(&session).map_err(storage_error)?;
+        Ok(session)
+    }
+
+    fn decode_proof(&self, c: &[u8], s: &[u8]) -> Result<Proof<G>, Status> {
+        let group = &self.zkp.group;
+        Ok(Proof {
+            c: group.decode_scalar_checked(c).map_err(|err| Status::invalid_argument(format!("Invalid c: {err}")))?,
+            s: group.decode_scalar_checked(s).map_err(|err| Status::invalid_argument(format!("Invalid s: {err}")))?,
+        })
+    }
+
+    // the registered keys of a user
+    fn statement(&self, user_info: &UserInfo) -> Result<Statement<G>, Status> {
+        let decode = |bytes: &[u8]| stored(self.zkp.group.decode_element(bytes), "element");
+        Ok(Statement { y1: decode(&user_info.y1)?, y2: decode(&user_info.y2)? })
+    }
+
+    // Counts a failed verification towards the lockout of `user`, the error to return for it
+    fn failed(&self, user: &str) -> Status {
+        if let Some(lock) = self.limits.failed(user) {
+            warn!(user, lock_secs = lock.as_secs(), "Locked account after failed logins");
+        }
+        Status::unauthenticated("User is Invalid")
+    }
+
+    // Refuses a proof that was already accepted before it is checked. Checked against keys
+    // rotated since, a replay would fail as a wrong proof and count towards a lockout.
+    fn unused_proof(&self, user: &str, c: &[u8]) -> Result<(), Status> {
+        if self.store.has_proof(user, c).map_err(storage_error)? {
+            Err(Status::unauthenticated("Proof was already used"))?
+        }
+        Ok(())
+    }
+
+    // Records a non-interactive proof that verified and refuses one that was accepted before.
+    // Its timestamp keeps it valid for LOGIN_MAX_SKEW_SECS, it is remembered for as long.
+    fn consume_proof(&self, user: &str, timestamp: u64, c: &[u8]) -> Result<(), Status> {
+        let expires_at = timestamp.saturating_add(LOGIN_MAX_SKEW_SECS);
+        if !self.store.insert_proof(user, c, expires_at).map_err(storage_error)? {
+            Err(Status::unauthenticated("Proof was already used"))?
+        }
+        Ok(())
+    }
+
+    // Rate limits a request that `authorize` checks, only proofs count against the user as
+    // anybody could use up their tokens otherwise
+    fn limit_authorized(&self, peer: Option<IpAddr>, user: &str, admin_token: &str) -> Result<(), Status> {
+        self.limits.check(peer, Some(user).filter(|_| admin_token.is_empty()))
+    }
+
+    // Checks that a request acting on `user` carries either the admin token or a fresh
+    // non-interactive proof for the keys of the user over `context`
+    fn authorize(&self, user: &str, admin_token: &str, timestamp: u64, c: &[u8], s: &[u8], context: &[u8]) -> Result<(), Status> {
+        if !admin_token.is_empty() {
+            return match &self.admin_token {
+                Some(expected) if tokens_match(expected, admin_token) => Ok(()),
+                _ => Err(Status::permission_denied("Invalid admin token")),
+            };
+        }
+
+        check_timestamp(timestamp)?;
+        let proof = self.decode_proof(c, s)?;
+        self.unused_proof(user, c)?;
+        let user_info = self
+            .store
+            .get_user(user)
+            .map_err(storage_error)?
+            .ok_or_else(|| Status::unauthenticated("Username does not exist"))?;
+        if !self.zkp.verify_noninteractive(user, &self.statement(&user_info)?, &proof, context) {
+            Err(self.failed(user))?
+        }
+        self.consume_proof(user, timestamp, c)?;
+        self.limits.succeeded(user);
+        Ok(())
+    }
+
+    // the session if it exists and has not expired
+    fn valid_session(&self, session_id: &str) -> Result<Session, Status> {
+        match self.store.get_session(session_id).map_err(storage_error)? {
+            Some(session) if session.expires_at > unix_now() => Ok(session),
+            _ => Err(Status::unauthenticated("Session is invalid or expired")),
+        }
+    }
+}
+
+// Deletes expired challenges, sessions and accepted proofs every `interval`. All are refused
+// or cannot be replayed once expired anyway, but they would otherwise pile up, as would the
+// rate limits of every user name and address ever seen.
+fn spawn_sweeper(store: Arc<dyn Store>, limits: Arc<Limits>, challenge_ttl: Duration, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval.max(Duration::from_secs(1)));
+        loop {
+            interval.tick().await;
+            let now = unix_now();
+            if let Err(err) = store.remove_expired_challenges(now.saturating_sub(challenge_ttl.as_secs())) {
+                warn!("Failed to remove expired challenges: {err}");
+            }
+            if let Err(err) = store.remove_expired_sessions(now) {
+                warn!("Failed to remove expired sessions: {err}");
+            }
+            if let Err(err) = store.remove_expired_proofs(now) {
+                warn!("Failed to remove expired proofs: {err}");
+            }
+            limits.sweep();
+        }
+    })
+}
+
+// the address a request came from, tonic knows it for TCP and TLS connections
+fn peer<T>(request: &Request<T>) -> Option<IpAddr> {
+    request.remote_addr().map(|addr| addr.ip())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("Clock is before epoch").as_secs()
+}
+
+// non-interactive proofs carry the time they were made at, refuse stale ones
+fn check_timestamp(timestamp: u64) -> Result<(), Status> {
+    if unix_now().abs_diff(timestamp) > LOGIN_MAX_SKEW_SECS {
+        Err(Status::unauthenticated("Login timestamp is too far from server time"))?
+    }
+    Ok(())
+}
+
+// Compares the digests so the time taken does not depend on how much of the token matched
+fn tokens_match(expected: &str, given: &str) -> bool {
+    Sha256::digest(expected) == Sha256::digest(given)
+}
+
+// A value the server stored itself, it only fails to decode if the database was altered
+fn stored<T>(value: Option<T>, name: &str) -> Result<T, Status> {
+    value.ok_or_else(|| Status::internal(format!("Stored {name} is invalid")))
+}
+
+fn storage_error(err: StoreError) -> Status {
+    Status::internal(format!("Storage error: {err}"))
+}
+
+#[tonic::async_trait]
+impl<G: Group + 'static> Auth for AuthImpl<G> {
+    async fn register(&self, request: Request<RegisterRequest>) -> Result<Response<RegisterResponse>, Status> {
+        self.limits.check(peer(&request), None)?;
+        let request = request.into_inner();
+        let username = request.user_name;
+
+        let group = &self.zkp.group;
+        group.decode_checked(&request.y1).map_err(|err| Status::invalid_argument(format!("Invalid y1: {err}")))?;
+        group.decode_checked(&request.y2).map_err(|err| Status::invalid_argument(format!("Invalid y2: {err}")))?;
+        if request.salt.len() < zkp::kdf::SALT_LEN {
+            Err(Status::invalid_argument("Invalid salt"))?
+        }
+        let user_info = UserInfo {
+            user_name: username.clone(),
+            y1: request.y1,
+            y2: request.y2,
+            salt: request.salt,
+        };
+
+        if !self.store.insert_user(&user_info).map_err(storage_error)? {
+            Err(Status::already_exists("Username already exists"))?
+        }
+        info!(user = %username, "Registered");
+
+        Ok(Response::new(RegisterResponse {}))
+    }
+
+    async fn create_authentication_challenge(&self, request: Request<AuthenticationChallengeRequest>) -> Result<Response<AuthenticationChallengeResponse>, Status> {
+        self.limits.check(peer(&request), Some(&request.get_ref().user))?;
+        let request = request.into_inner();
+        let username = request.user;
+        let group = &self.zkp.group;
+        group.decode_checked(&request.r1).map_err(|err| Status::invalid_argument(format!("Invalid r1: {err}")))?;
+        group.decode_checked(&request.r2).map_err(|err| Status::invalid_argument(format!("Invalid r2: {err}")))?;
+
+        if let Some(user_info) = self.store.get_user(&username).map_err(storage_error)? {
+            let c  = group.random_scalar();
+            let challenge = PendingChallenge {
+                auth_id: generate_random_string(12),
+                user: user_info.user_name,
+                r1: request.r1,
+                r2: request.r2,
+                c: group.encode_scalar(&c),
+                created_at: unix_now(),
+            };
+            self.store.put_challenge(&challenge).map_err(storage_error)?;
+
+            Ok(Response::new(AuthenticationChallengeResponse { auth_id: challenge.auth_id, c: challenge.c, salt: user_info.salt }))
+        } else {
+            Err(Status::unauthenticated("Username does not exist"))?
+        }
+    }
+
+    async fn verify_authentication(&self, request: Request<AuthenticationAnswerRequest>) -> Result<Response<AuthenticationAnswerResponse>, Status> {
+        self.limits.check(peer(&request), None)?;
+        let request = request.into_inner();
+        let auth_id = request.auth_id;
+
+        // the challenge is consumed by the first answer, whether it is right or wrong
+        if let Some(challenge) = self.store.take_challenge(&auth_id).map_err(storage_error)? {
+            if unix_now().saturating_sub(challenge.created_at) > self.challenge_ttl.as_secs() {
+                Err(Status::unauthenticated("Challenge has expired"))?
+            }
+            let user_info = self
+                .store
+                .get_user(&challenge.user)
+                .map_err(storage_error)?
+                .ok_or_else(|| Status::unauthenticated("Username does not exist"))?;
+            // the challenge may have been handed out before the account was locked
+            self.limits.check_locked(&user_info.user_name)?;
+            let zkp = &self.zkp;
+            let s = zkp.group.decode_scalar_checked(&request.s).map_err(|err| Status::invalid_argument(format!("Invalid s: {err}")))?;
+
+            let decode = |bytes: &[u8]| stored(zkp.group.decode_element(bytes), "element");
+            let c = stored(zkp.group.decode_scalar(&challenge.c), "challenge")?;
+            let statement = self.statement(&user_info)?;
+            let verification = zkp.verify(&decode(&challenge.r1)?, &decode(&challenge.r2)?, &statement.y1, &statement.y2, &c, &s);
+
+            if verification {
+                self.limits.succeeded(&user_info.user_name);
+                Ok(Response::new(self.logged_in(&user_info.user_name, Some(auth_id))?))
+            } else {
+                Err(self.failed(&user_info.user_name))?
+            }
+
+        } else {
+            Err(Status::unauthenticated("AuthId does not exist"))?
+        }
+    }
+
+    async fn login(&self, request: Request<LoginRequest>) -> Result<Response<AuthenticationAnswerResponse>, Status> {
+        self.limits.check(peer(&request), Some(&request.get_ref().user))?;
+        let request = request.into_inner();
+
+        check_timestamp(request.timestamp)?;
+        let zkp = &self.zkp;
+        let proof = self.decode_proof(&request.c, &request.s)?;
+        self.unused_proof(&request.user, &request.c)?;
+
+        if let Some(user_info) = self.store.get_user(&request.user).map_err(storage_error)? {
+            let context = login_context(request.timestamp);
+            let verification = zkp.verify_noninteractive(&request.user, &self.statement(&user_info)?, &proof, &context);
+
+            if verification {
+                self.consume_proof(&user_info.user_name, request.timestamp, &request.c)?;
+                self.limits.succeeded(&user_info.user_name);
+                Ok(Response::new(self.logged_in(&user_info.user_name, None)?))
+            } else {
+                Err(self.failed(&user_info.user_name))?
+            }
+        } else {
+            Err(Status::unauthenticated("Username does not exist"))?
+        }
+    }
+
+    async fn validate_session(&self, request: Request<ValidateSessionRequest>) -> Result<Response<ValidateSessionResponse>, Status> {
+        let session = self.valid_session(&request.into_inner().session_id)?;
+        Ok(Response::new(ValidateSessionResponse { user: session.user, expires_at: session.expires_at }))
+    }
+
+    async fn refresh_session(&self, request: Request<RefreshSessionRequest>) -> Result<Response<RefreshSessionResponse>, Status> {
+        let session = self.valid_session(&request.into_inner().session_id)?;
+        // only one of two concurrent refreshes of the same session gets to replace it
+        if !self.store.remove_session(&session.session_id).map_err(storage_error)? {
+            Err(Status::unauthenticated("Session is invalid or expired"))?
+        }
+        let session = self.issue_session(&session.user)?;
+        // tokens carry the expiry, the old one would be refused before the new session ends
+        let token = self.sign_token(&session, None)?.unwrap_or_default();
+        Ok(Response::new(RefreshSessionResponse { session_id: session.session_id, expires_at: session.expires_at, token }))
+    }
+
+    async fn logout(&self, request: Request<LogoutRequest>) -> Result<Response<LogoutResponse>, Status> {
+        // logging out of an unknown or expired session is not an error, the outcome is the same
+        self.store.remove_session(&request.into_inner().session_id).map_err(storage_error)?;
+        Ok(Response::new(LogoutResponse {}))
+    }
+
+    async fn rotate_credentials(&self, request: Request<RotateCredentialsRequest>) -> Result<Response<RotateCredentialsResponse>, Status> {
+        self.limits.check(peer(&request), Some(&request.get_ref().user))?;
+        let request = request.into_inner();
+
+        check_timestamp(request.timestamp)?;
+        let group = &self.zkp.group;
+        let proof = self.decode_proof(&request.c, &request.s)?;
+        group.decode_checked(&request.new_y1).map_err(|err| Status::invalid_argument(format!("Invalid new_y1: {err}")))?;
+        group.decode_checked(&request.new_y2).map_err(|err| Status::invalid_argument(format!("Invalid new_y2: {err}")))?;
+        if request.new_salt.len() < zkp::kdf::SALT_LEN {
+            Err(Status::invalid_argument("Invalid new_salt"))?
+        }
+        self.unused_proof(&request.user, &request.c)?;
+
+        if let Some(user_info) = self.store.get_user(&request.user).map_err(storage_error)? {
+            let context = rotate_context(request.timestamp, &request.new_y1, &request.new_y2, &request.new_salt);
+            if !self.zkp.verify_noninteractive(&request.user, &self.statement(&user_info)?, &proof, &context) {
+                Err(self.failed(&request.user))?
+            }
+            self.consume_proof(&request.user, request.timestamp, &request.c)?;
+            self.limits.succeeded(&request.user);
+
+            let rotated = UserInfo { user_name: request.user, y1: request.new_y1, y2: request.new_y2, salt: request.new_salt };
+            // fails if the keys the proof was checked against were rotated in the meantime
+            if !self.store.replace_user(&user_info, &rotated).map_err(storage_error)? {
+                Err(Status::aborted("Credentials were changed concurrently"))?
+            }
+            info!(user = %user_info.user_name, "Rotated credentials");
+            Ok(Response::new(RotateCredentialsResponse {}))
+        } else {
+            Err(Status::unauthenticated("Username does not exist"))?
+        }
+    }
+
+    async fn delete_account(&self, request: Request<DeleteAccountRequest>) -> Result<Response<DeleteAccountResponse>, Status> {
+        self.limit_authorized(peer(&request), &request.get_ref().user, &request.get_ref().admin_token)?;
+        let request = request.into_inner();
+
+        let context = delete_context(request.timestamp);
+        self.authorize(&request.user, &request.admin_token, request.timestamp, &request.c, &request.s, &context)?;
+        if !self.store.delete_account(&request.user).map_err(storage_error)? {
+            Err(Status::not_found("Username does not exist"))?
+        }
+        info!(user = %request.user, admin = !request.admin_token.is_empty(), "Deleted account");
+        Ok(Response::new(DeleteAccountResponse {}))
+    }
+
+    async fn revoke_sessions(&self, request: Request<RevokeSessionsRequest>) -> Result<Response<RevokeSessionsResponse>, Status> {
+        self.limit_authorized(peer(&request), &request.get_ref().user, &request.get_ref().admin_token)?;
+        let request = request.into_inner();
+
+        let context = revoke_context(request.timestamp);
+        self.authorize(&request.user, &request.admin_token, request.timestamp, &request.c, &request.s, &context)?;
+        let revoked = self.store.remove_user_sessions(&request.user).map_err(storage_error)?;
+        info!(user = %request.user, revoked, "Revoked sessions");
+        Ok(Response::new(RevokeSessionsResponse { revoked: revoked as u64 }))
+    }
+
+    async fn get_public_keys(&self, _request: Request<PublicKeysRequest>) -> Result<Response<PublicKeysResponse>, Status> {
+        let keys = self
+            .token_signer
+            .iter()
+            .map(|signer| {
+                let key = signer.public_key().clone();
+                PublicKey { kid: key.kid, kty: key.kty, crv: key.crv, alg: key.alg, x: key.x }
+            })
+            .collect();
+        Ok(Response::new(PublicKeysResponse { keys }))
+    }
+
+    async fn get_salt(&self, request: Request<SaltRequest>) -> Result<Response<SaltResponse>, Status> {
+        self.limits.check(peer(&request), None)?;
+        let request = request.into_inner();
+
+        if let Some(user_info) = self.store.get_user(&request.user).map_err(storage_error)? {
+            Ok(Response::new(SaltResponse { salt: user_info.salt }))
+        } else {
+            Err(Status::unauthenticated("Username does not exist"))?
+        }
+    }
+}
+
+async fn serve<G: Group + 'static>(config: Config, zkp: ZKP<G>) -> Result<(), Box<dyn Error>> {
+    let store = store::open(config.db.as_deref(), &config.group).map_err(|err| format!("Could not open store: {err}"))?;
+    let mut auth_impl = AuthImpl::new(zkp, store.clone());
+    auth_impl.challenge_ttl = config.challenge_ttl;
+    auth_impl.session_ttl = config.session_ttl;
+    if let Some(path) = &config.token_key {
+        let signer = TokenSigner::from_file(path).map_err(|err| format!("Invalid token key {}: {err}", path.display()))?;
+        auth_impl.token_signer = Some(signer);
+    }
+    auth_impl.admin_token = config.admin_token;
+    auth_impl.limits = Arc::new(Limits::new(config.user_rate, config.peer_rate, config.lockout));
+    spawn_sweeper(store, auth_impl.limits.clone(), auth_impl.challenge_ttl, auth_impl.challenge_ttl);
+
+    let mut server = Server::builder();
+    if let Some(tls) = &config.tls {
+        let read = |path: &std::path::Path| std::fs::read(path).map_err(|err| format!("Could not read {}: {err}", path.display()));
+        let mut tls_config = ServerTlsConfig::new().identity(Identity::from_pem(read(&tls.cert)?, read(&tls.key)?));
+        // clients without a certificate signed by this CA are refused during the handshake
+        if let Some(client_ca) = &tls.client_ca {
+            tls_config = tls_config.client_ca_root(Certificate::from_pem(read(client_ca)?));
+        }
+        server = server.tls_config(tls_config).map_err(|err| format!("Invalid TLS configuration: {err}"))?;
+    }
+
+    let mtls = config.tls.as_ref().is_some_and(|tls| tls.client_ca.is_some());
+    info!(listen = %config.listen, group = %config.group, tls = config.tls.is_some(), mtls, "Serving");
+    server.add_service(AuthServer::new(auth_impl)).serve(config.listen).await?;
+    Ok(())
+}
+
+async fn run(config: Config) -> Result<(), Box<dyn Error>> {
+    match config.group.as_str() {
+        "ristretto255" => {
+            let (alpha, beta) = RistrettoGroup::generators();
+            serve(config, ZKP::new(RistrettoGroup::new(), alpha, beta)).await
+        }
+        name => {
+            let zkp = ParameterSet::by_name(name)
+                .and_then(|set| set.zkp())
+                .map_err(|err| format!("Invalid parameter set {name}: {err}"))?;
+            serve(config, zkp).await
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    // settings come from flags, the environment and the file named by --config, in that order
+    let config = Config::load(Args::parse()).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(2);
+    });
+    tracing_subscriber::fmt().with_env_filter(EnvFilter::new(&config.log_level)).init();
+
+    if let Err(err) = run(config).await {
+        tracing::error!("{err}");
+        std::process::exit(1);
+    }
+}
@@ -1,71 +1,345 @@
-pub mod zkp_auth {
-    include!("./zkp_auth.rs");
+use std::fmt;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use serde_json::{json, Map, Value};
+use tonic::Code;
+
+use zkp::sdk::{ClientError, Session, TlsOptions};
+use zkp::{Group, ParameterSet, RistrettoGroup, ZkpAuthClient, ZKP};
+
+#[derive(Parser)]
+#[command(version, about = "ZKP authentication client")]
+struct Cli {
+    #[arg(long, global = true, env = "ZKP_SERVER", default_value = "http://127.0.0.1:50051", help = "URL of the auth server, https:// connects over TLS")]
+    server: String,
+
+    #[command(flatten)]
+    tls: Tls,
+
+    #[arg(long, global = true, env = "ZKP_GROUP", default_value = zkp::params::DEFAULT_PARAMETER_SET.name, help = "parameter set or `ristretto255`, it must match the one used by the server")]
+    group: String,
+
+    #[arg(long, global = true, help = "print the result, or the error, as one JSON object on stdout")]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
 }
 
-use std::io::stdin;
-use num_bigint::BigUint;
-use zkp_auth::auth_client;
-use crate::zkp_auth::auth_client::AuthClient;
-use crate::zkp_auth::{AuthenticationAnswerRequest, AuthenticationChallengeRequest, AuthenticationChallengeResponse, RegisterRequest};
+// TLS settings, used when the server URL is https://
+#[derive(Args)]
+struct Tls {
+    #[arg(long, global = true, env = "ZKP_CA_CERT", help = "PEM CA certificate the server certificate is checked against, the system roots by default")]
+    ca_cert: Option<PathBuf>,
 
-use zkp::ZKP;
+    #[arg(long, global = true, env = "ZKP_CLIENT_CERT", requires = "client_key", help = "PEM client certificate, for servers that require mutual TLS")]
+    client_cert: Option<PathBuf>,
 
-#[tokio::main]
-async fn main() {
+    #[arg(long, global = true, env = "ZKP_CLIENT_KEY", requires = "client_cert", help = "PEM private key of the client certificate")]
+    client_key: Option<PathBuf>,
+
+    #[arg(long, global = true, env = "ZKP_TLS_DOMAIN", help = "name the server certificate must be valid for, the host of the URL by default")]
+    tls_domain: Option<String>,
+}
 
-    let (alpha,beta,p,q) = ZKP::get_constants();
-    let zkp = ZKP { alpha, beta, p, q};
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Register a user, the secret is derived from the password with a fresh salt")]
+    Register(Credentials),
 
-    let mut buf = String::new();
-    let mut client = AuthClient::connect("http://127.0.0.1:50051").await.expect("Failed to connect");
-    println!("Hello from client");
+    #[command(about = "Log in and print the session")]
+    Login {
+        #[command(flatten)]
+        credentials: Credentials,
 
-    println!("Please provide username");
-    stdin().read_line(&mut buf).expect("Failed to read line");
-    let username = buf.trim().to_string();
-    buf.clear();
-    println!("Please provide password");
-    stdin().read_line(&mut buf).expect("Failed to read line");
-    let password = BigUint::from_bytes_be(buf.trim().as_bytes());
+        #[arg(long, help = "send a single Fiat-Shamir proof instead of answering a challenge")]
+        non_interactive: bool,
+    },
 
-    let y1 = ZKP::exponentiate(&zkp.alpha, &password, &zkp.p);
-    let y2 = ZKP::exponentiate(&zkp.beta, &password, &zkp.p);
+    #[command(about = "Replace the password of a user, authorized by a proof for the current one")]
+    Rotate {
+        #[command(flatten)]
+        credentials: Credentials,
 
-    let request = RegisterRequest {
-        user_name: username.clone(),
-        y1: y1.to_bytes_be(),
-        y2: y2.to_bytes_be(),
-    };
-    let _response = client.register(request).await.expect("Failed to send request");
-    println!("✅ Registration was successful");
+        #[command(flatten)]
+        new_password: NewPassword,
+    },
+
+    #[command(about = "End a session")]
+    Logout(SessionId),
+
+    #[command(about = "Print the user a session belongs to and when it expires")]
+    Whoami(SessionId),
+
+    #[command(about = "Print the public parameters of the group")]
+    Params,
+}
 
-    buf.clear();
+#[derive(Args)]
+struct Credentials {
+    #[arg(short, long, env = "ZKP_USER")]
+    user: String,
 
-    println!("Please provide password to login");
-    stdin().read_line(&mut buf).expect("Failed to read line");
-    let password = BigUint::from_bytes_be(buf.trim().as_bytes());
+    #[command(flatten)]
+    password: Password,
+}
+
+// Where the password comes from, a prompt without echo unless one of these is given
+#[derive(Args)]
+struct Password {
+    #[arg(long, value_name = "VAR", conflicts_with = "password_fd", help = "read the password from this environment variable")]
+    password_env: Option<String>,
+
+    #[arg(long, value_name = "FD", value_parser = clap::value_parser!(i32).range(0..), help = "read the password from the first line of this file descriptor")]
+    password_fd: Option<i32>,
+}
+
+#[derive(Args)]
+struct NewPassword {
+    #[arg(long, value_name = "VAR", conflicts_with = "new_password_fd", help = "read the new password from this environment variable")]
+    new_password_env: Option<String>,
+
+    #[arg(long, value_name = "FD", value_parser = clap::value_parser!(i32).range(0..), help = "read the new password from the first line of this file descriptor")]
+    new_password_fd: Option<i32>,
+}
+
+#[derive(Args)]
+struct SessionId {
+    #[arg(short, long, env = "ZKP_SESSION", help = "session id printed by login")]
+    session: String,
+}
+
+// A failed command, with the gRPC status code when the server refused it
+#[derive(Debug)]
+struct CliError {
+    code: Option<Code>,
+    message: String,
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{} ({code:?})", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError { code: None, message }
+    }
+}
 
-    let k = ZKP::generate_random_below(&zkp.q);
-    let r1 = ZKP::exponentiate(&zkp.alpha, &k, &zkp.p);
-    let r2 = ZKP::exponentiate(&zkp.beta, &k, &zkp.p);
+impl From<ClientError> for CliError {
+    fn from(err: ClientError) -> Self {
+        match err {
+            ClientError::Status(status) => CliError { code: Some(status.code()), message: status.message().to_string() },
+            err => err.to_string().into(),
+        }
+    }
+}
 
-    let request = AuthenticationChallengeRequest {
-        user: username.clone(),
-        r1: r1.to_bytes_be(),
-        r2: r2.to_bytes_be(),
+impl CliError {
+    fn print(&self, json: bool) {
+        if json {
+            let code = self.code.map(|code| format!("{code:?}"));
+            println!("{}", json!({ "error": self.message, "code": code }));
+        } else {
+            eprintln!("Error: {self}");
+        }
+    }
+}
+
+// Result of a command: a summary and named values, printed as lines or as one JSON object
+struct Output {
+    summary: &'static str,
+    fields: Map<String, Value>,
+}
+
+impl Output {
+    fn new(summary: &'static str) -> Self {
+        Output { summary, fields: Map::new() }
+    }
+
+    fn field(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.fields.insert(name.to_string(), value.into());
+        self
+    }
+
+    fn print(self, json: bool) {
+        if json {
+            println!("{}", Value::Object(self.fields));
+            return;
+        }
+        println!("{}", self.summary);
+        for (name, value) in self.fields {
+            match value {
+                // strings without the JSON quotes
+                Value::String(value) => println!("  {name}: {value}"),
+                value => println!("  {name}: {value}"),
+            }
+        }
+    }
+}
+
+impl Tls {
+    fn is_set(&self) -> bool {
+        self.ca_cert.is_some() || self.client_cert.is_some() || self.tls_domain.is_some()
+    }
+
+    fn options(&self) -> Result<TlsOptions, CliError> {
+        let read = |path: &PathBuf| std::fs::read(path).map_err(|err| CliError::from(format!("Could not read {}: {err}", path.display())));
+        let identity = match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => Some((read(cert)?, read(key)?)),
+            _ => None,
+        };
+        Ok(TlsOptions { ca_cert: self.ca_cert.as_ref().map(read).transpose()?, identity, domain: self.tls_domain.clone() })
+    }
+}
+
+impl Password {
+    fn read(&self, prompt: &str, confirm: bool) -> Result<String, CliError> {
+        read_password(&self.password_env, self.password_fd, prompt, confirm)
+    }
+}
+
+impl NewPassword {
+    fn read(&self) -> Result<String, CliError> {
+        read_password(&self.new_password_env, self.new_password_fd, "New password: ", true)
+    }
+}
+
+// Reads a password from an environment variable, a file descriptor or the terminal.
+// A prompted password that is about to be registered is asked for twice.
+fn read_password(env: &Option<String>, fd: Option<i32>, prompt: &str, confirm: bool) -> Result<String, CliError> {
+    if let Some(var) = env {
+        return std::env::var(var).map_err(|_| format!("{var} is not set").into());
+    }
+    if let Some(fd) = fd {
+        return read_fd(fd).map_err(|err| format!("Could not read password from fd {fd}: {err}").into());
+    }
+    let password = rpassword::prompt_password(prompt).map_err(|err| format!("Could not read password: {err}"))?;
+    if confirm && rpassword::prompt_password("Repeat password: ").map_err(|err| format!("Could not read password: {err}"))? != password {
+        Err("Passwords do not match".to_string())?
+    }
+    Ok(password)
+}
+
+#[cfg(unix)]
+fn read_fd(fd: i32) -> std::io::Result<String> {
+    use std::io::{BufRead, BufReader, Error, ErrorKind};
+    use std::os::fd::BorrowedFd;
+
+    // the output goes there, a password cannot come from them
+    if fd == 1 || fd == 2 {
+        return Err(Error::new(ErrorKind::InvalidInput, "stdout and stderr are not password sources"));
+    }
+    // SAFETY: the descriptor is only borrowed to duplicate it, which fails with EBADF when it is
+    // not open. Only the duplicate is owned and closed here, the original is left alone.
+    let file = std::fs::File::from(unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()?);
+    let mut line = String::new();
+    BufReader::new(file).read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: i32) -> std::io::Result<String> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "file descriptors are only supported on unix"))
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let json = cli.json;
+
+    let group = cli.group.clone();
+    let result = match group.as_str() {
+        "ristretto255" => {
+            let (alpha, beta) = RistrettoGroup::generators();
+            run(cli, ZKP::new(RistrettoGroup::new(), alpha, beta)).await
+        }
+        name => match ParameterSet::by_name(name).and_then(|set| set.zkp()) {
+            Ok(zkp) => run(cli, zkp).await,
+            Err(err) => Err(format!("Invalid parameter set {name}: {err}").into()),
+        },
     };
-    let response = client.create_authentication_challenge(request).await.expect("Failed to send request").into_inner();
-    println!("{:#?}", response);
 
-    let auth_id = response.auth_id;
-    let c = BigUint::from_bytes_be(&response.c);
+    match result {
+        Ok(output) => output.print(json),
+        Err(err) => {
+            err.print(json);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn run<G: Group>(cli: Cli, zkp: ZKP<G>) -> Result<Output, CliError> {
+    if let Command::Params = cli.command {
+        return Ok(params(&cli.group, &zkp));
+    }
 
-    let s = zkp.solve(&k, &c, &password);
-    let request = AuthenticationAnswerRequest {
-        auth_id,
-        s: s.to_bytes_be(),
+    let mut client = if cli.server.starts_with("https://") {
+        ZkpAuthClient::connect_tls(cli.server, &cli.tls.options()?, zkp).await?
+    } else if cli.tls.is_set() {
+        Err("TLS options need an https:// server URL".to_string())?
+    } else {
+        ZkpAuthClient::connect(cli.server, zkp).await?
     };
-    let response = client.verify_authentication(request).await.expect("Failed to verify").into_inner();
-    println!("You logged in successfully: {:#?}", response.session_id);
+    match cli.command {
+        Command::Register(credentials) => {
+            let password = credentials.password.read("Password: ", true)?;
+            client.register(&credentials.user, password).await?;
+            Ok(Output::new("Registered").field("user", credentials.user))
+        }
+        Command::Login { credentials, non_interactive } => {
+            let password = credentials.password.read("Password: ", false)?;
+            let session = if non_interactive {
+                client.login_noninteractive(&credentials.user, password).await?
+            } else {
+                client.login(&credentials.user, password).await?
+            };
+            Ok(logged_in(credentials.user, session))
+        }
+        Command::Rotate { credentials, new_password } => {
+            let password = credentials.password.read("Current password: ", false)?;
+            let new_password = new_password.read()?;
+            client.rotate(&credentials.user, password, new_password).await?;
+            Ok(Output::new("Rotated credentials").field("user", credentials.user))
+        }
+        Command::Logout(session) => {
+            client.logout(&session.session).await?;
+            Ok(Output::new("Logged out"))
+        }
+        Command::Whoami(session) => {
+            let info = client.validate_session(&session.session).await?;
+            Ok(Output::new("Session is valid").field("user", info.user).field("expires_at", info.expires_at))
+        }
+        Command::Params => unreachable!("handled before connecting"),
+    }
+}
 
-}
\ No newline at end of file
+fn params<G: Group>(name: &str, zkp: &ZKP<G>) -> Output {
+    let mut output = Output::new("Group parameters")
+        .field("group", name)
+        .field("element_len", zkp.group.element_len())
+        .field("scalar_len", zkp.group.scalar_len());
+    if let Ok(set) = ParameterSet::by_name(name) {
+        output = output.field("p", hex::encode(set.p().to_bytes_be())).field("q", hex::encode(set.q().to_bytes_be()));
+    }
+    output
+        .field("alpha", hex::encode(zkp.group.encode_element(&zkp.alpha)))
+        .field("beta", hex::encode(zkp.group.encode_element(&zkp.beta)))
+}
+
+fn logged_in(user: String, session: Session) -> Output {
+    let output = Output::new("Logged in")
+        .field("user", user)
+        .field("session_id", session.session_id)
+        .field("expires_at", session.expires_at);
+    match session.token {
+        Some(token) => output.field("token", token),
+        None => output,
+    }
+}
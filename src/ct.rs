@@ -0,0 +1,89 @@
+// Constant-time arithmetic modulo the p and q of a mod p group, used for every
+// computation that involves a secret (the password derived x and the nonce k).
+//
+// Values are held in fixed-size Montgomery form, so multiplications, additions
+// and table lookups run in the same time and touch the same memory whatever the
+// operands are. Converting from and to BigUint at the boundary is only variable
+// time in the number of limbs of the BigUint, which leaks at most how many
+// leading zero words a scalar has.
+use crypto_bigint::modular::runtime_mod::{DynResidue, DynResidueParams};
+use crypto_bigint::subtle::{ConditionallySelectable, ConstantTimeEq};
+use crypto_bigint::{Encoding, Limb, Word, U3072};
+use num_bigint::BigUint;
+
+// Widest modulus supported by the constant-time arithmetic
+pub const MAX_MODULUS_BITS: u64 = 3072;
+
+const LIMBS: usize = U3072::LIMBS;
+
+pub(crate) type Uint = U3072;
+pub(crate) type Residue = DynResidue<LIMBS>;
+
+// An odd modulus of at most MAX_MODULUS_BITS bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Modulus {
+    params: DynResidueParams<LIMBS>,
+}
+
+impl Modulus {
+    pub fn new(modulus: &BigUint) -> Self {
+        assert!(modulus.bit(0), "modulus must be odd");
+        Modulus { params: DynResidueParams::new(&to_uint(modulus)) }
+    }
+
+    // n mod m, for any n below 2^MAX_MODULUS_BITS
+    pub fn residue(&self, n: &BigUint) -> Residue {
+        self.residue_of(&to_uint(n))
+    }
+
+    pub fn residue_of(&self, n: &Uint) -> Residue {
+        DynResidue::new(n, self.params)
+    }
+
+    pub fn montgomery(&self, n: Uint) -> Residue {
+        DynResidue::from_montgomery(n, self.params)
+    }
+
+    pub fn one(&self) -> Residue {
+        DynResidue::one(self.params)
+    }
+}
+
+pub(crate) fn to_uint(n: &BigUint) -> Uint {
+    assert!(n.bits() <= MAX_MODULUS_BITS, "value is wider than {MAX_MODULUS_BITS} bits");
+    let mut bytes = [0u8; Uint::BYTES];
+    let le = n.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    Uint::from_le_bytes(bytes)
+}
+
+// big-endian bytes, at most Uint::BYTES of them
+pub(crate) fn from_be_bytes(bytes: &[u8]) -> Uint {
+    assert!(bytes.len() <= Uint::BYTES, "value is wider than {MAX_MODULUS_BITS} bits");
+    let mut padded = [0u8; Uint::BYTES];
+    padded[Uint::BYTES - bytes.len()..].copy_from_slice(bytes);
+    Uint::from_be_bytes(padded)
+}
+
+pub(crate) fn to_biguint(n: &Uint) -> BigUint {
+    BigUint::from_bytes_le(&n.to_le_bytes())
+}
+
+pub(crate) fn retrieve(n: &Residue) -> BigUint {
+    to_biguint(&n.retrieve())
+}
+
+// The `width` bits of `n` starting at bit `offset`, which must not straddle a word
+pub(crate) fn digit(n: &Uint, offset: usize, width: usize) -> Word {
+    let word = n.as_words()[offset / Limb::BITS];
+    (word >> (offset % Limb::BITS)) & ((1 << width) - 1)
+}
+
+// entries[index], reading every entry so the memory access pattern does not depend on index
+pub(crate) fn select(entries: &[Uint], index: Word) -> Uint {
+    let mut out = Uint::ZERO;
+    for (j, entry) in entries.iter().enumerate() {
+        out.conditional_assign(entry, (j as Word).ct_eq(&index));
+    }
+    out
+}
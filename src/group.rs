@@ -0,0 +1,375 @@
+use std::fmt::Debug;
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha512};
+
+use crate::ct::{self, Modulus, Uint};
+use crate::generate_random_below;
+use crate::params::ParameterError;
+use crate::proof::EncodingError;
+
+pub use crate::ct::MAX_MODULUS_BITS;
+
+// A cyclic group of prime order q in which the Chaum-Pedersen protocol runs.
+// Elements are combined with `op` (written multiplicatively), scalars live in Z_q.
+// Encodings are canonical: every element and every scalar has a fixed width.
+pub trait Group: Send + Sync {
+    type Element: Clone + PartialEq + Debug + Send + Sync;
+    type Scalar: Clone + PartialEq + Debug + Send + Sync;
+    // precomputed powers of a fixed base
+    type Table: Send + Sync;
+
+    fn identity(&self) -> Self::Element;
+
+    // a * b
+    fn op(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    // base ^ exponent
+    fn exp(&self, base: &Self::Element, exponent: &Self::Scalar) -> Self::Element;
+
+    // uniformly random scalar in [0, q)
+    fn random_scalar(&self) -> Self::Scalar;
+
+    // a * b mod q
+    fn scalar_mul(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    // a - b mod q
+    fn scalar_sub(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    // a + b mod q
+    fn scalar_add(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    // -a mod q
+    fn scalar_neg(&self, a: &Self::Scalar) -> Self::Scalar;
+
+    fn precompute(&self, base: &Self::Element) -> Self::Table;
+
+    // base ^ exponent using the table of a base of order q
+    fn exp_fixed(&self, table: &Self::Table, exponent: &Self::Scalar) -> Self::Element;
+
+    // a ^ x * b ^ y where `table` holds the powers of a. By default the table is
+    // used for a, groups with a fast simultaneous exponentiation should override this.
+    fn exp2(&self, _a: &Self::Element, table: &Self::Table, x: &Self::Scalar, b: &Self::Element, y: &Self::Scalar) -> Self::Element {
+        self.op(&self.exp_fixed(table, x), &self.exp(b, y))
+    }
+
+    // base_1 ^ e_1 * ... * base_n ^ e_n, implementations should share work between the terms
+    fn multi_exp(&self, terms: &[(Self::Element, Self::Scalar)]) -> Self::Element {
+        terms
+            .iter()
+            .fold(self.identity(), |acc, (base, exponent)| self.op(&acc, &self.exp(base, exponent)))
+    }
+
+    // SHA-512(bytes) reduced mod q
+    fn hash_to_scalar(&self, bytes: &[u8]) -> Self::Scalar;
+
+    // width in bytes of the uniformly random input of `scalar_from_wide_bytes`
+    fn wide_scalar_len(&self) -> usize {
+        self.scalar_len() + 32
+    }
+
+    // `wide_scalar_len()` uniformly random bytes reduced mod q, the result is
+    // uniform up to a bias of 2^-256. Runs in constant time.
+    fn scalar_from_wide_bytes(&self, bytes: &[u8]) -> Self::Scalar;
+
+    // Maps a seed to a non-identity element whose discrete log nobody knows
+    fn hash_to_element(&self, seed: &[u8]) -> Self::Element;
+
+    // Canonical description of the group, bound into Fiat-Shamir challenges
+    fn transcript_label(&self) -> Vec<u8>;
+
+    // width in bytes of an encoded element / scalar
+    fn element_len(&self) -> usize;
+    fn scalar_len(&self) -> usize;
+
+    fn encode_element(&self, element: &Self::Element) -> Vec<u8>;
+    fn decode_element(&self, bytes: &[u8]) -> Option<Self::Element>;
+
+    fn encode_scalar(&self, scalar: &Self::Scalar) -> Vec<u8>;
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<Self::Scalar>;
+
+    // whether a decoded element lies in the order q subgroup
+    fn in_subgroup(&self, element: &Self::Element) -> bool;
+
+    // Decodes an element received from a peer, rejecting wrong widths, non-canonical
+    // encodings, elements outside the order q subgroup and the identity
+    fn decode_checked(&self, bytes: &[u8]) -> Result<Self::Element, EncodingError> {
+        if bytes.len() != self.element_len() {
+            return Err(EncodingError::InvalidLength { expected: self.element_len(), actual: bytes.len() });
+        }
+        let element = self.decode_element(bytes).ok_or(EncodingError::InvalidElement)?;
+        if !self.in_subgroup(&element) {
+            return Err(EncodingError::NotInSubgroup);
+        }
+        if element == self.identity() {
+            return Err(EncodingError::Identity);
+        }
+        Ok(element)
+    }
+
+    // Decodes a scalar received from a peer, rejecting wrong widths and values not below q
+    fn decode_scalar_checked(&self, bytes: &[u8]) -> Result<Self::Scalar, EncodingError> {
+        if bytes.len() != self.scalar_len() {
+            return Err(EncodingError::InvalidLength { expected: self.scalar_len(), actual: bytes.len() });
+        }
+        self.decode_scalar(bytes).ok_or(EncodingError::InvalidScalar)
+    }
+}
+
+// Re-derives a generator from its public seed, so anyone can audit that
+// nobody knows its discrete log with respect to the other generator
+pub fn verify_generator<G: Group>(group: &G, seed: &[u8], generator: &G::Element) -> bool {
+    group.hash_to_element(seed) == *generator
+}
+
+// Width in bits of the windows used by the mod p exponentiation tables
+const WINDOW: u64 = 4;
+
+// digit of `exponent` in window `window`
+fn window_digit(exponent: &BigUint, window: u64) -> usize {
+    (0..WINDOW).fold(0, |digit, i| digit | (exponent.bit(window * WINDOW + i) as usize) << i)
+}
+
+// rows[i][j] = base ^ (j * 2^(WINDOW * i)) in Montgomery form, one row per window
+// of an exponent below q, so an exponentiation is one multiplication per window
+// and no squarings
+#[derive(Clone, Debug)]
+pub struct ModPTable {
+    rows: Vec<[Uint; 1 << WINDOW]>,
+}
+
+// Order-q subgroup of the multiplicative group Z_p^*.
+// Operations on scalars and on fixed bases run in constant time, they are the
+// ones that see the secret x and the nonce k. Variable-base exponentiations
+// (`exp`, `multi_exp`) are variable time and only meant for public values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModPGroup {
+    pub p: BigUint,
+    pub q: BigUint,
+    p_ct: Modulus,
+    q_ct: Modulus,
+}
+
+impl ModPGroup {
+    // Fails unless p and q are odd and at most MAX_MODULUS_BITS wide. It does not check
+    // that they are prime or that q divides p - 1, `ParameterSet::validate` does.
+    pub fn new(p: BigUint, q: BigUint) -> Result<Self, ParameterError> {
+        for (name, modulus) in [("p", &p), ("q", &q)] {
+            if modulus.bits() > MAX_MODULUS_BITS {
+                return Err(ParameterError::TooLarge(name));
+            }
+            if !modulus.bit(0) {
+                return Err(ParameterError::Even(name));
+            }
+        }
+        let p_ct = Modulus::new(&p);
+        let q_ct = Modulus::new(&q);
+        Ok(ModPGroup { p, q, p_ct, q_ct })
+    }
+}
+
+impl Group for ModPGroup {
+    type Element = BigUint;
+    type Scalar = BigUint;
+    type Table = ModPTable;
+
+    fn identity(&self) -> BigUint {
+        BigUint::from(1u32)
+    }
+
+    fn op(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.p
+    }
+
+    fn exp(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        base.modpow(exponent, &self.p)
+    }
+
+    fn random_scalar(&self) -> BigUint {
+        generate_random_below(&self.q)
+    }
+
+    fn scalar_mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        ct::retrieve(&(self.q_ct.residue(a) * self.q_ct.residue(b)))
+    }
+
+    fn scalar_sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        ct::retrieve(&(self.q_ct.residue(a) - self.q_ct.residue(b)))
+    }
+
+    fn scalar_add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        ct::retrieve(&(self.q_ct.residue(a) + self.q_ct.residue(b)))
+    }
+
+    fn scalar_neg(&self, a: &BigUint) -> BigUint {
+        ct::retrieve(&-self.q_ct.residue(a))
+    }
+
+    fn precompute(&self, base: &BigUint) -> ModPTable {
+        let mut power = self.p_ct.residue(base);
+        let rows = (0..self.q.bits().div_ceil(WINDOW))
+            .map(|_| {
+                let mut row = [*self.p_ct.one().as_montgomery(); 1 << WINDOW];
+                let mut entry = self.p_ct.one();
+                for slot in row.iter_mut().skip(1) {
+                    entry *= power;
+                    *slot = *entry.as_montgomery();
+                }
+                power = entry * power;
+                row
+            })
+            .collect();
+        ModPTable { rows }
+    }
+
+    // Every window costs one table scan and one multiplication, including the
+    // windows whose digit is 0.
+    fn exp_fixed(&self, table: &ModPTable, exponent: &BigUint) -> BigUint {
+        let exponent = self.q_ct.residue(exponent).retrieve();
+        let mut acc = self.p_ct.one();
+        for (window, row) in table.rows.iter().enumerate() {
+            let digit = ct::digit(&exponent, window * WINDOW as usize, WINDOW as usize);
+            acc *= self.p_ct.montgomery(ct::select(row, digit));
+        }
+        ct::retrieve(&acc)
+    }
+
+    // Straus simultaneous exponentiation through `multi_exp`, the two exponentiations share
+    // one chain of squarings. Only ever used on public values so variable time is fine,
+    // the table of a is for constant-time callers and goes unused.
+    fn exp2(&self, a: &BigUint, _table: &ModPTable, x: &BigUint, b: &BigUint, y: &BigUint) -> BigUint {
+        self.multi_exp(&[(a.clone(), x.clone()), (b.clone(), y.clone())])
+    }
+
+    // Straus: one table of base^0..base^15 per term, then a single shared chain
+    // of squarings consuming every exponent 4 bits at a time
+    fn multi_exp(&self, terms: &[(BigUint, BigUint)]) -> BigUint {
+        let tables: Vec<Vec<BigUint>> = terms
+            .iter()
+            .map(|(base, _)| {
+                let base = base % &self.p;
+                let mut table = vec![BigUint::from(1u32), base.clone()];
+                for j in 2..1 << WINDOW {
+                    table.push((&table[j - 1] * &base) % &self.p);
+                }
+                table
+            })
+            .collect();
+
+        let bits = terms.iter().map(|(_, exponent)| exponent.bits()).max().unwrap_or(0);
+        let mut acc = BigUint::from(1u32);
+        for window in (0..bits.div_ceil(WINDOW)).rev() {
+            for _ in 0..WINDOW {
+                acc = (&acc * &acc) % &self.p;
+            }
+            for (table, (_, exponent)) in tables.iter().zip(terms) {
+                let digit = window_digit(exponent, window);
+                if digit != 0 {
+                    acc = (&acc * &table[digit]) % &self.p;
+                }
+            }
+        }
+        acc
+    }
+
+    fn hash_to_scalar(&self, bytes: &[u8]) -> BigUint {
+        ct::retrieve(&self.q_ct.residue(&BigUint::from_bytes_be(&Sha512::digest(bytes))))
+    }
+
+    // big-endian bytes = high * 2^256 + low with both halves below 2^3072
+    fn scalar_from_wide_bytes(&self, bytes: &[u8]) -> BigUint {
+        assert_eq!(bytes.len(), self.wide_scalar_len(), "wrong number of bytes to reduce");
+        let (high, low) = bytes.split_at(bytes.len() - 32);
+        let shift = self.q_ct.residue(&(BigUint::from(1u32) << 256));
+        let high = self.q_ct.residue_of(&ct::from_be_bytes(high));
+        let low = self.q_ct.residue_of(&ct::from_be_bytes(low));
+        ct::retrieve(&(high * shift + low))
+    }
+
+    // h = H(seed || counter) expanded to |p| + 128 bits, element = h ^ ((p - 1) / q) mod p,
+    // retried with the next counter in the negligible case that this lands on 1
+    fn hash_to_element(&self, seed: &[u8]) -> BigUint {
+        let one = BigUint::from(1u32);
+        let cofactor = (&self.p - &one) / &self.q;
+        let len = self.p.bits().div_ceil(8) as usize + 16;
+
+        for counter in 0u32.. {
+            let mut bytes = Vec::with_capacity(len + 64);
+            for block in 0u32.. {
+                if bytes.len() >= len {
+                    break;
+                }
+                let mut hasher = Sha512::new();
+                hasher.update(seed);
+                hasher.update(counter.to_be_bytes());
+                hasher.update(block.to_be_bytes());
+                bytes.extend_from_slice(&hasher.finalize());
+            }
+            bytes.truncate(len);
+
+            let element = (BigUint::from_bytes_be(&bytes) % &self.p).modpow(&cofactor, &self.p);
+            if element != one && element != BigUint::ZERO {
+                return element;
+            }
+        }
+        unreachable!("hash to group exhausted its counter")
+    }
+
+    fn transcript_label(&self) -> Vec<u8> {
+        let p = self.p.to_bytes_be();
+        let q = self.q.to_bytes_be();
+        let mut label = b"modp".to_vec();
+        label.extend_from_slice(&(p.len() as u32).to_be_bytes());
+        label.extend_from_slice(&p);
+        label.extend_from_slice(&(q.len() as u32).to_be_bytes());
+        label.extend_from_slice(&q);
+        label
+    }
+
+    fn element_len(&self) -> usize {
+        self.p.bits().div_ceil(8) as usize
+    }
+
+    fn scalar_len(&self) -> usize {
+        self.q.bits().div_ceil(8) as usize
+    }
+
+    // big-endian, left padded to the width of p
+    fn encode_element(&self, element: &BigUint) -> Vec<u8> {
+        to_fixed_bytes(element, self.element_len())
+    }
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<BigUint> {
+        if bytes.len() != self.element_len() {
+            return None;
+        }
+        let element = BigUint::from_bytes_be(bytes);
+        (element < self.p).then_some(element)
+    }
+
+    // big-endian, left padded to the width of q
+    fn encode_scalar(&self, scalar: &BigUint) -> Vec<u8> {
+        to_fixed_bytes(scalar, self.scalar_len())
+    }
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<BigUint> {
+        if bytes.len() != self.scalar_len() {
+            return None;
+        }
+        let scalar = BigUint::from_bytes_be(bytes);
+        (scalar < self.q).then_some(scalar)
+    }
+
+    // 0 < element < p and element ^ q = 1
+    fn in_subgroup(&self, element: &BigUint) -> bool {
+        *element != BigUint::ZERO && *element < self.p && element.modpow(&self.q, &self.p) == BigUint::from(1u32)
+    }
+}
+
+fn to_fixed_bytes(n: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    assert!(bytes.len() <= len, "value does not fit in {len} bytes");
+    let mut out = vec![0u8; len - bytes.len()];
+    out.extend_from_slice(&bytes);
+    out
+}
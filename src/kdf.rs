@@ -0,0 +1,68 @@
+use std::fmt;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+
+use crate::group::Group;
+
+// Length of the salts generated at registration
+pub const SALT_LEN: usize = 16;
+
+// Argon2id cost parameters. Client and server do not exchange them, every
+// client must derive the secret with the same values it registered with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    // memory in KiB
+    pub memory: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+// OWASP's minimum recommendation for Argon2id: 19 MiB, 2 iterations, 1 lane
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KdfError {
+    SaltTooShort,
+    InvalidParams(String),
+}
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KdfError::SaltTooShort => write!(f, "salt must be at least {} bytes", argon2::MIN_SALT_LEN),
+            KdfError::InvalidParams(reason) => write!(f, "invalid Argon2 parameters: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for KdfError {}
+
+pub fn generate_salt() -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+// x = Argon2id(password, salt) stretched to `wide_scalar_len()` bytes and reduced mod q,
+// so x is uniform in [0, q) up to a bias of 2^-256
+pub fn derive_secret<G: Group>(group: &G, password: &[u8], salt: &[u8], params: &KdfParams) -> Result<G::Scalar, KdfError> {
+    if salt.len() < argon2::MIN_SALT_LEN {
+        return Err(KdfError::SaltTooShort);
+    }
+    let mut wide = vec![0u8; group.wide_scalar_len()];
+    let params = Params::new(params.memory, params.iterations, params.parallelism, Some(wide.len()))
+        .map_err(|err| KdfError::InvalidParams(err.to_string()))?;
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password, salt, &mut wide)
+        .map_err(|err| KdfError::InvalidParams(err.to_string()))?;
+    Ok(group.scalar_from_wide_bytes(&wide))
+}
@@ -2,95 +2,251 @@ use num_bigint::{BigUint, RandBigInt};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 
-pub struct ZKP {
-    pub q: BigUint,
-    pub p: BigUint,
-    pub alpha: BigUint,
-    pub beta: BigUint,
+mod ct;
+pub mod group;
+pub mod kdf;
+pub mod nonce;
+pub mod params;
+pub mod proof;
+pub mod ristretto;
+#[cfg(feature = "grpc")]
+pub mod sdk;
+pub mod store;
+pub mod token;
+
+// gRPC bindings generated from proto/zkp_auth.proto
+#[cfg(feature = "grpc")]
+pub mod zkp_auth {
+    tonic::include_proto!("zkp_auth");
 }
 
-impl ZKP {
-    // alpha ^ x mod p
-    // output = n ^ exp mod p
-    pub fn exponentiate(n: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
-        n.modpow(exponent, modulus)
+pub use group::{Group, ModPGroup};
+pub use kdf::{derive_secret, generate_salt, KdfError, KdfParams};
+pub use nonce::NonceStrategy;
+pub use params::{ParameterError, ParameterSet};
+pub use proof::{CanonicalEncoding, Commitment, EncodingError, Proof, Statement};
+pub use ristretto::RistrettoGroup;
+#[cfg(feature = "grpc")]
+pub use sdk::ZkpAuthClient;
+
+// Domain separation tag of the Fiat-Shamir transcript
+const FIAT_SHAMIR_TAG: &[u8] = b"zkp-grpc/chaum-pedersen/fiat-shamir";
+
+// Context the prover binds a non-interactive login proof to
+pub fn login_context(timestamp: u64) -> Vec<u8> {
+    format!("zkp_auth/login/{timestamp}").into_bytes()
+}
+
+// Context of the proof against the old keys that authorizes a credential rotation,
+// it binds the new keys so the proof cannot install any others
+pub fn rotate_context(timestamp: u64, new_y1: &[u8], new_y2: &[u8], new_salt: &[u8]) -> Vec<u8> {
+    let mut context = format!("zkp_auth/rotate/{timestamp}").into_bytes();
+    for part in [new_y1, new_y2, new_salt] {
+        context.extend_from_slice(&(part.len() as u32).to_be_bytes());
+        context.extend_from_slice(part);
     }
+    context
+}
 
+// Context of the proof that authorizes deleting an account
+pub fn delete_context(timestamp: u64) -> Vec<u8> {
+    format!("zkp_auth/delete/{timestamp}").into_bytes()
+}
 
-    // output  = s = k - c * x mod q
-    pub fn solve(&self, k: &BigUint, c: &BigUint, x: &BigUint) -> BigUint {
-        if *k >= c * x {
-            (k - c * x).modpow(&BigUint::from(1u32), &self.q)
-        } else {
-            (&self.q - (c * x - k)).modpow(&BigUint::from(1u32), &self.q)
+// Context of the proof that authorizes revoking every session of a user
+pub fn revoke_context(timestamp: u64) -> Vec<u8> {
+    format!("zkp_auth/revoke/{timestamp}").into_bytes()
+}
+
+pub struct ZKP<G: Group> {
+    pub group: G,
+    pub alpha: G::Element,
+    pub beta: G::Element,
+    // fixed-base tables, every exponentiation of alpha and beta goes through them
+    alpha_table: G::Table,
+    beta_table: G::Table,
+}
+
+impl<G: Group> ZKP<G> {
+    pub fn new(group: G, alpha: G::Element, beta: G::Element) -> Self {
+        let alpha_table = group.precompute(&alpha);
+        let beta_table = group.precompute(&beta);
+        ZKP { group, alpha, beta, alpha_table, beta_table }
+    }
+
+    // output = n ^ exp
+    // variable time, secret exponents of alpha and beta go through statement and commitment
+    pub fn exponentiate(&self, n: &G::Element, exponent: &G::Scalar) -> G::Element {
+        self.group.exp(n, exponent)
+    }
+
+    // y1 = alpha ^ x, y2 = beta ^ x
+    pub fn statement(&self, x: &G::Scalar) -> Statement<G> {
+        Statement {
+            y1: self.group.exp_fixed(&self.alpha_table, x),
+            y2: self.group.exp_fixed(&self.beta_table, x),
+        }
+    }
+
+    // r1 = alpha ^ k, r2 = beta ^ k
+    pub fn commitment(&self, k: &G::Scalar) -> Commitment<G> {
+        Commitment {
+            r1: self.group.exp_fixed(&self.alpha_table, k),
+            r2: self.group.exp_fixed(&self.beta_table, k),
         }
     }
 
+    // output  = s = k - c * x mod q
+    pub fn solve(&self, k: &G::Scalar, c: &G::Scalar, x: &G::Scalar) -> G::Scalar {
+        self.group.scalar_sub(k, &self.group.scalar_mul(c, x))
+    }
+
     // cond1: r1 = alpha ^ s * y1^c
     // cond2: r2 = beta ^ s * y2^c
-    pub fn verify(&self, r1: &BigUint, r2: &BigUint,y1: &BigUint, y2: &BigUint, c: &BigUint, s: &BigUint) -> bool {
-        let cond1 = *r1 == (&self.alpha.modpow(s, &self.p) * y1.modpow(c, &self.p)).modpow(&BigUint::from(1u32), &self.p);
-        let cond2 = *r2 == (&self.beta.modpow(s, &self.p) * y2.modpow(c, &self.p)).modpow(&BigUint::from(1u32), &self.p);
+    pub fn verify(&self, r1: &G::Element, r2: &G::Element, y1: &G::Element, y2: &G::Element, c: &G::Scalar, s: &G::Scalar) -> bool {
+        let cond1 = *r1 == self.group.exp2(&self.alpha, &self.alpha_table, s, y1, c);
+        let cond2 = *r2 == self.group.exp2(&self.beta, &self.beta_table, s, y2, c);
         cond1 && cond2
     }
 
-    pub fn generate_random_below(limit: &BigUint) -> BigUint {
-        let mut rng = rand::thread_rng();
-        let random = rng.gen_biguint_below(limit);
-        random
+    pub fn verify_proof(&self, statement: &Statement<G>, commitment: &Commitment<G>, proof: &Proof<G>) -> bool {
+        self.verify(&commitment.r1, &commitment.r2, &statement.y1, &statement.y2, &proof.c, &proof.s)
     }
 
-    pub fn get_constants() -> (BigUint, BigUint, BigUint, BigUint) {
-        let p = BigUint::from_bytes_be(
-            &hex::decode("B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371").expect("Invalid hex string"),
-        );
-        let q = BigUint::from_bytes_be(
-            &hex::decode("801C0D34C58D93FE997177101F80535A4738CEBCBF389A99B36371EB").expect("Invalid hex string"),
-        );
+    // Verifies many interactive proofs at once. With random weights w_i, v_i every
+    // r1_i = alpha^s_i * y1_i^c_i and r2_i = beta^s_i * y2_i^c_i is folded into
+    //   alpha^(sum w_i s_i) * beta^(sum v_i s_i) * prod y1_i^(w_i c_i) * y2_i^(v_i c_i) * r1_i^-w_i * r2_i^-v_i = 1
+    // which one multi-exponentiation checks. A bad proof only passes with probability 1/q.
+    // Elements must lie in the order q subgroup. On failure each proof is checked on
+    // its own and the indices of the invalid ones are returned.
+    pub fn verify_batch(&self, proofs: &[(Statement<G>, Commitment<G>, Proof<G>)]) -> Result<(), Vec<usize>> {
+        let group = &self.group;
+        let sum = |acc: Option<G::Scalar>, term: G::Scalar| match acc {
+            Some(acc) => Some(group.scalar_add(&acc, &term)),
+            None => Some(term),
+        };
 
-        let alpha = BigUint::from_bytes_be(
-            &hex::decode("AC4032EF4F2D9AE39DF30B5C8FFDAC506CDEBE7B89998CAF74866A08CFE4FFE3A6824A4E10B9A6F0DD921F01A70C4AFAAB739D7700C29F52C57DB17C620A8652BE5E9001A8D66AD7C17669101999024AF4D027275AC1348BB8A762D0521BC98AE247150422EA1ED409939D54DA7460CDB5F6C6B250717CBEF180EB34118E98D119529A45D6F834566E3025E316A330EFBB77A86F0C1AB15B051AE3D428C8F8ACB70A8137150B8EEB10E183EDD19963DDD9E263E4770589EF6AA21E7F5F2FF381B539CCE3409D13CD566AFBB48D6C019181E1BCFE94B30269EDFE72FE9B6AA4BD7B5A0F1C71CFFF4C19C418E1F6EC017981BC087F2A7065B384B890D3191F2BFA").expect("Invalid hex string"),
-        );
-        let exp =  BigUint::from_bytes_be(&hex::decode("266FEA1E5C41564B777E69").unwrap());
+        let mut alpha_exponent = None;
+        let mut beta_exponent = None;
+        let mut terms = Vec::with_capacity(4 * proofs.len());
+        for (statement, commitment, proof) in proofs {
+            let w = group.random_scalar();
+            let v = group.random_scalar();
+            alpha_exponent = sum(alpha_exponent, group.scalar_mul(&w, &proof.s));
+            beta_exponent = sum(beta_exponent, group.scalar_mul(&v, &proof.s));
+            terms.push((statement.y1.clone(), group.scalar_mul(&w, &proof.c)));
+            terms.push((statement.y2.clone(), group.scalar_mul(&v, &proof.c)));
+            terms.push((commitment.r1.clone(), group.scalar_neg(&w)));
+            terms.push((commitment.r2.clone(), group.scalar_neg(&v)));
+        }
+        let (Some(alpha_exponent), Some(beta_exponent)) = (alpha_exponent, beta_exponent) else {
+            return Ok(());
+        };
+        let alpha_power = group.exp_fixed(&self.alpha_table, &alpha_exponent);
+        let beta_power = group.exp_fixed(&self.beta_table, &beta_exponent);
 
-        // beta = alpha^i is also a generator
-        let beta = alpha.modpow(&exp, &p);
+        if group.op(&group.op(&group.multi_exp(&terms), &alpha_power), &beta_power) == group.identity() {
+            return Ok(());
+        }
 
-        (alpha, beta, p, q)
+        let invalid: Vec<usize> = proofs
+            .iter()
+            .enumerate()
+            .filter(|(_, (statement, commitment, proof))| !self.verify_proof(statement, commitment, proof))
+            .map(|(i, _)| i)
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
     }
 
-    pub fn generate_random_string(size: usize) -> String {
-        let mut rng = rand::thread_rng();
-        rng.sample_iter(&Alphanumeric).take(size).map(char::from).collect()
+    // c = H(params, user, y1, y2, r1, r2, context)
+    pub fn challenge(&self, user: &str, statement: &Statement<G>, commitment: &Commitment<G>, context: &[u8]) -> G::Scalar {
+        let mut transcript = Vec::new();
+        for part in [
+            FIAT_SHAMIR_TAG,
+            &self.group.transcript_label(),
+            &self.group.encode_element(&self.alpha),
+            &self.group.encode_element(&self.beta),
+            user.as_bytes(),
+            &statement.to_bytes(&self.group),
+            &commitment.to_bytes(&self.group),
+            context,
+        ] {
+            transcript.extend_from_slice(&(part.len() as u32).to_be_bytes());
+            transcript.extend_from_slice(part);
+        }
+        self.group.hash_to_scalar(&transcript)
+    }
+
+    // Nonce k for a proof of knowledge of x. The derivation also binds the group and
+    // the generators, so the same x and context give unrelated nonces in other groups.
+    pub fn nonce(&self, strategy: NonceStrategy, x: &G::Scalar, context: &[u8]) -> G::Scalar {
+        let mut data = Vec::new();
+        for part in [
+            &self.group.transcript_label(),
+            &self.group.encode_element(&self.alpha),
+            &self.group.encode_element(&self.beta),
+            context,
+        ] {
+            data.extend_from_slice(&(part.len() as u32).to_be_bytes());
+            data.extend_from_slice(part);
+        }
+        strategy.nonce(&self.group, x, &data)
+    }
+
+    // Fiat-Shamir: the prover computes the challenge itself
+    pub fn prove_noninteractive(&self, user: &str, x: &G::Scalar, context: &[u8]) -> Proof<G> {
+        self.prove_noninteractive_with(user, x, context, NonceStrategy::Random)
     }
-}
 
-// // alpha ^ x mod p
-// // output = n ^ exp mod p
-// pub fn exponentiate(n: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
-//     n.modpow(exponent, modulus)
-// }
+    // Deterministic nonces are safe here: the challenge is a function of the user and
+    // the context, so equal nonces always come with equal challenges
+    pub fn prove_noninteractive_with(&self, user: &str, x: &G::Scalar, context: &[u8], strategy: NonceStrategy) -> Proof<G> {
+        let statement = self.statement(x);
 
-// // output  = s = k - c * x mod q
-// pub fn solve(k: &BigUint, c: &BigUint, x: &BigUint,  q: &BigUint) -> BigUint {
-//     if *k >= c * x {
-//         (k - c * x).modpow(&BigUint::from(1u32), q)
-//     } else {
-//         (q - (c * x - k)).modpow(&BigUint::from(1u32), q)
-//     }
-// }
+        let mut nonce_context = Vec::new();
+        for part in [user.as_bytes(), context] {
+            nonce_context.extend_from_slice(&(part.len() as u32).to_be_bytes());
+            nonce_context.extend_from_slice(part);
+        }
+        let k = self.nonce(strategy, x, &nonce_context);
+        let commitment = self.commitment(&k);
+
+        let c = self.challenge(user, &statement, &commitment, context);
+        let s = self.solve(&k, &c, x);
+        Proof { c, s }
+    }
 
-// // cond1: r1 = alpha ^ s * y1^c
-// // cond2: r2 = beta ^ s * y2^c
-// pub fn verify(r1: &BigUint, r2: &BigUint,y1: &BigUint, y2: &BigUint, alpha: &BigUint,  beta: &BigUint, c: &BigUint, s: &BigUint, p: &BigUint) -> bool {
-//     let cond1 = *r1 == (alpha.modpow(s, p) * y1.modpow(c, p)).modpow(&BigUint::from(1u32), &p);
-//     let cond2 = *r2 == (beta.modpow(s, p) * y2.modpow(c, p)).modpow(&BigUint::from(1u32), &p);
-//     cond1 && cond2
-// }
+    // r1 = alpha ^ s * y1^c
+    // r2 = beta ^ s * y2^c
+    // accept if c = H(params, user, y1, y2, r1, r2, context)
+    pub fn verify_noninteractive(&self, user: &str, statement: &Statement<G>, proof: &Proof<G>, context: &[u8]) -> bool {
+        let Proof { c, s } = proof;
+        let commitment = Commitment {
+            r1: self.group.exp2(&self.alpha, &self.alpha_table, s, &statement.y1, c),
+            r2: self.group.exp2(&self.beta, &self.beta_table, s, &statement.y2, c),
+        };
+        *c == self.challenge(user, statement, &commitment, context)
+    }
+}
 
+pub fn generate_random_below(limit: &BigUint) -> BigUint {
+    let mut rng = rand::thread_rng();
+    rng.gen_biguint_below(limit)
+}
 
+pub fn generate_random_string(size: usize) -> String {
+    let rng = rand::thread_rng();
+    rng.sample_iter(&Alphanumeric).take(size).map(char::from).collect()
+}
 
+#[cfg(test)]
 mod test {
+    use serde::de::DeserializeSeed;
+
     use super::*;
 
     #[test]
@@ -99,26 +255,21 @@ mod test {
         let beta = BigUint::from(9u32);
         let p = BigUint::from(23u32);
         let q = BigUint::from(11u32);
-        let zkp = ZKP {
-            q: q.clone(),
-            p: p.clone(),
-            alpha: alpha.clone(),
-            beta: beta.clone(),
-        };
+        let zkp = ZKP::new(ModPGroup::new(p.clone(), q.clone()).unwrap(), alpha.clone(), beta.clone());
 
         let x = BigUint::from(6u32);
         let k = BigUint::from(7u32);
 
         let c = BigUint::from(4u32);
 
-        let y1 = ZKP::exponentiate(&alpha, &x, &p);
-        let y2 = ZKP::exponentiate(&beta, &x, &p);
+        let y1 = zkp.exponentiate(&alpha, &x);
+        let y2 = zkp.exponentiate(&beta, &x);
 
         assert_eq!(y1, BigUint::from(2u32));
         assert_eq!(y2, BigUint::from(3u32));
 
-        let r1 = ZKP::exponentiate(&alpha, &k, &p);
-        let r2 = ZKP::exponentiate(&beta, &k, &p);
+        let r1 = zkp.exponentiate(&alpha, &k);
+        let r2 = zkp.exponentiate(&beta, &k);
         assert_eq!(r1, BigUint::from(8u32));
         assert_eq!(r2, BigUint::from(4u32));
 
@@ -128,8 +279,11 @@ mod test {
         let x_fake = BigUint::from(7u32);
         let s_fake = zkp.solve(&k, &c, &x_fake);
 
+        let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s);
+        assert!(result);
+
         let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s_fake);
-        assert_eq!(!result, false);
+        assert!(!result);
     }
 
     #[test]
@@ -138,27 +292,22 @@ mod test {
         let beta = BigUint::from(9u32);
         let p = BigUint::from(23u32);
         let q = BigUint::from(11u32);
-        let zkp = ZKP {
-            q: q.clone(),
-            p: p.clone(),
-            alpha: alpha.clone(),
-            beta: beta.clone(),
-        };
+        let zkp = ZKP::new(ModPGroup::new(p.clone(), q.clone()).unwrap(), alpha.clone(), beta.clone());
 
         let x = BigUint::from(6u32);
-        let k = ZKP::generate_random_below(&q);
+        let k = generate_random_below(&q);
 
-        let c = ZKP::generate_random_below( &q);
+        let c = generate_random_below(&q);
 
-        let y1 = ZKP::exponentiate(&alpha, &x, &p);
-        let y2 = ZKP::exponentiate(&beta, &x, &p);
+        let y1 = zkp.exponentiate(&alpha, &x);
+        let y2 = zkp.exponentiate(&beta, &x);
 
-        let r1 = ZKP::exponentiate(&alpha, &k, &p);
-        let r2 = ZKP::exponentiate(&beta, &k, &p);
+        let r1 = zkp.exponentiate(&alpha, &k);
+        let r2 = zkp.exponentiate(&beta, &k);
         let s = zkp.solve(&k, &c, &x);
 
         let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s);
-        assert_eq!(result, true);
+        assert!(result);
     }
 
 
@@ -192,29 +341,552 @@ mod test {
             &hex::decode("B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371").expect("Invalid hex string"),
         );
         let q = BigUint::from_bytes_be(
-            &hex::decode("801C0D34C58D93FE997177101F80535A4738CEBCBF389A99B36371EB").expect("Invalid hex string"),
+            &hex::decode("F518AA8781A8DF278ABA4E7D64B7CB9D49462353").expect("Invalid hex string"),
         );
 
         let alpha = BigUint::from_bytes_be(
-            &hex::decode("AC4032EF4F2D9AE39DF30B5C8FFDAC506CDEBE7B89998CAF74866A08CFE4FFE3A6824A4E10B9A6F0DD921F01A70C4AFAAB739D7700C29F52C57DB17C620A8652BE5E9001A8D66AD7C17669101999024AF4D027275AC1348BB8A762D0521BC98AE247150422EA1ED409939D54DA7460CDB5F6C6B250717CBEF180EB34118E98D119529A45D6F834566E3025E316A330EFBB77A86F0C1AB15B051AE3D428C8F8ACB70A8137150B8EEB10E183EDD19963DDD9E263E4770589EF6AA21E7F5F2FF381B539CCE3409D13CD566AFBB48D6C019181E1BCFE94B30269EDFE72FE9B6AA4BD7B5A0F1C71CFFF4C19C418E1F6EC017981BC087F2A7065B384B890D3191F2BFA").expect("Invalid hex string"),
+            &hex::decode("A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5").expect("Invalid hex string"),
         );
 
         // beta = alpha^i is also a generator
-        let beta = alpha.modpow(&ZKP::generate_random_below(&q), &p);
+        let beta = alpha.modpow(&generate_random_below(&q), &p);
+
+        let zkp = ZKP::new(ModPGroup::new(p.clone(), q.clone()).unwrap(), alpha.clone(), beta.clone());
+
+        let x = generate_random_below(&q);
+        let k = generate_random_below(&q);
+
+        let c = generate_random_below(&q);
+
+        let y1 = zkp.exponentiate(&alpha, &x);
+        let y2 = zkp.exponentiate(&beta, &x);
+
+        let r1 = zkp.exponentiate(&alpha, &k);
+        let r2 = zkp.exponentiate(&beta, &k);
+
+        let s = zkp.solve(&k, &c, &x);
+
+        let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s);
+        assert!(result);
+    }
+
+    #[test]
+    fn test_ristretto_example() {
+        let (alpha, beta) = RistrettoGroup::generators();
+        let zkp = ZKP::new(RistrettoGroup::new(), alpha, beta);
+
+        let x = zkp.group.random_scalar();
+        let k = zkp.group.random_scalar();
+        let c = zkp.group.random_scalar();
+
+        let y1 = zkp.exponentiate(&zkp.alpha, &x);
+        let y2 = zkp.exponentiate(&zkp.beta, &x);
+        assert_eq!(zkp.group.encode_element(&y1).len(), 32);
 
-        let zkp = ZKP {
-            q: q.clone(),
-            p: p.clone(),
-            alpha: alpha.clone(),
-            beta: beta.clone(),
+        let r1 = zkp.exponentiate(&zkp.alpha, &k);
+        let r2 = zkp.exponentiate(&zkp.beta, &k);
+
+        let s = zkp.solve(&k, &c, &x);
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+
+        let s_fake = zkp.solve(&k, &c, &zkp.group.random_scalar());
+        assert!(!zkp.verify(&r1, &r2, &y1, &y2, &c, &s_fake));
+    }
+
+    #[test]
+    fn test_parameter_sets_are_valid() {
+        for set in params::PARAMETER_SETS {
+            assert_eq!(set.validate(), Ok(()), "{}", set.name);
+            assert_eq!(ParameterSet::by_name(set.name), Ok(*set));
+        }
+        assert!(ParameterSet::by_name("rfc5114-512").is_err());
+    }
+
+    #[test]
+    fn test_inconsistent_parameter_set_is_rejected() {
+        // 1024-bit prime from RFC 5114 2.1 mixed with q and generator of RFC 5114 2.2
+        let mixed = ParameterSet::new(
+            "mixed",
+            "B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371",
+            "801C0D34C58D93FE997177101F80535A4738CEBCBF389A99B36371EB",
+            "AC4032EF4F2D9AE39DF30B5C8FFDAC506CDEBE7B89998CAF74866A08CFE4FFE3A6824A4E10B9A6F0DD921F01A70C4AFAAB739D7700C29F52C57DB17C620A8652BE5E9001A8D66AD7C17669101999024AF4D027275AC1348BB8A762D0521BC98AE247150422EA1ED409939D54DA7460CDB5F6C6B250717CBEF180EB34118E98D119529A45D6F834566E3025E316A330EFBB77A86F0C1AB15B051AE3D428C8F8ACB70A8137150B8EEB10E183EDD19963DDD9E263E4770589EF6AA21E7F5F2FF381B539CCE3409D13CD566AFBB48D6C019181E1BCFE94B30269EDFE72FE9B6AA4BD7B5A0F1C71CFFF4C19C418E1F6EC017981BC087F2A7065B384B890D3191F2BFA",
+        );
+        assert_eq!(mixed.validate(), Err(ParameterError::OrderDoesNotDivide));
+
+        let composite = ParameterSet::new("composite", "15", "B", "4");
+        assert_eq!(composite.validate(), Err(ParameterError::NotPrime("p")));
+
+        let bad_generator = ParameterSet::new("bad-generator", "17", "B", "5");
+        assert_eq!(bad_generator.validate(), Err(ParameterError::NotAGenerator("alpha")));
+
+        // moduli the group cannot work with are errors, not panics
+        let even_order = ParameterSet::new("even-order", "17", "2", "16");
+        assert_eq!(even_order.validate(), Err(ParameterError::Even("q")));
+        assert_eq!(even_order.zkp().err(), Some(ParameterError::Even("q")));
+        let wide = "F".repeat(769).leak();
+        assert_eq!(ParameterSet::new("wide-p", wide, "B", "4").validate(), Err(ParameterError::TooLarge("p")));
+        assert_eq!(ParameterSet::new("wide-q", "17", wide, "4").validate(), Err(ParameterError::TooLarge("q")));
+        assert_eq!(ModPGroup::new(BigUint::from(24u32), BigUint::from(11u32)), Err(ParameterError::Even("p")));
+    }
+
+    #[test]
+    fn test_beta_is_derived_from_seed() {
+        let set = params::RFC5114_1024_160;
+        let beta = set.beta().unwrap();
+        assert!(set.verify_beta(&beta));
+        assert!(!set.verify_beta(&set.alpha().modpow(&BigUint::from(2u32), &set.p())));
+        assert_eq!(beta.modpow(&set.q(), &set.p()), BigUint::from(1u32));
+
+        let (_, beta) = RistrettoGroup::generators();
+        assert!(group::verify_generator(&RistrettoGroup::new(), ristretto::BETA_SEED, &beta));
+        assert!(!group::verify_generator(&RistrettoGroup::new(), b"another seed", &beta));
+    }
+
+    #[test]
+    fn test_noninteractive_proof() {
+        let zkp = params::RFC5114_1024_160.zkp().unwrap();
+        let x = zkp.group.random_scalar();
+        let statement = zkp.statement(&x);
+        let context = login_context(1700000000);
+
+        let proof = zkp.prove_noninteractive("alice", &x, &context);
+        assert!(zkp.verify_noninteractive("alice", &statement, &proof, &context));
+        assert!(!zkp.verify_noninteractive("bob", &statement, &proof, &context));
+        assert!(!zkp.verify_noninteractive("alice", &statement, &proof, &login_context(1700000001)));
+
+        let proof_fake = zkp.prove_noninteractive("alice", &zkp.group.random_scalar(), &context);
+        assert!(!zkp.verify_noninteractive("alice", &statement, &proof_fake, &context));
+    }
+
+    #[test]
+    fn test_noninteractive_proof_ristretto() {
+        let (alpha, beta) = RistrettoGroup::generators();
+        let zkp = ZKP::new(RistrettoGroup::new(), alpha, beta);
+        let x = zkp.group.random_scalar();
+        let statement = zkp.statement(&x);
+        let swapped = Statement { y1: statement.y2, y2: statement.y1 };
+
+        let proof = zkp.prove_noninteractive("alice", &x, b"context");
+        assert!(zkp.verify_noninteractive("alice", &statement, &proof, b"context"));
+        assert!(!zkp.verify_noninteractive("alice", &swapped, &proof, b"context"));
+    }
+
+    #[test]
+    fn test_canonical_encoding() {
+        let zkp = params::RFC5114_2048_224.zkp().unwrap();
+        let x = zkp.group.random_scalar();
+        let statement = zkp.statement(&x);
+        let commitment = zkp.commitment(&zkp.group.random_scalar());
+        let proof = zkp.prove_noninteractive("alice", &x, b"context");
+
+        // fixed width, even when the leading bytes happen to be zero
+        assert_eq!(statement.to_bytes(&zkp.group).len(), 2 * 256);
+        assert_eq!(commitment.to_bytes(&zkp.group).len(), 2 * 256);
+        assert_eq!(proof.to_bytes(&zkp.group).len(), 2 * 28);
+        let small = Proof::<ModPGroup> { c: BigUint::from(1u32), s: BigUint::from(2u32) };
+        assert_eq!(small.to_hex(&zkp.group), format!("{}01{}02", "00".repeat(27), "00".repeat(27)));
+
+        assert_eq!(Statement::from_bytes(&zkp.group, &statement.to_bytes(&zkp.group)), Ok(statement.clone()));
+        assert_eq!(Commitment::from_hex(&zkp.group, &commitment.to_hex(&zkp.group)), Ok(commitment));
+        assert_eq!(Proof::from_base64(&zkp.group, &proof.to_base64(&zkp.group)), Ok(proof.clone()));
+
+        assert_eq!(
+            Proof::<ModPGroup>::from_bytes(&zkp.group, &[0u8; 10]),
+            Err(EncodingError::InvalidLength { expected: 56, actual: 10 })
+        );
+        assert_eq!(Proof::<ModPGroup>::from_hex(&zkp.group, "zz"), Err(EncodingError::InvalidHex));
+        assert_eq!(Proof::<ModPGroup>::from_bytes(&zkp.group, &[0xff; 56]), Err(EncodingError::InvalidScalar));
+
+        // serde writes the canonical encoding as hex and decodes it with the same checks
+        let json = serde_json::to_string(&proof.encoded(&zkp.group)).unwrap();
+        assert_eq!(json, format!("\"{}\"", proof.to_hex(&zkp.group)));
+        let decoded = Proof::decoder(&zkp.group).deserialize(&mut serde_json::Deserializer::from_str(&json));
+        assert_eq!(decoded.unwrap(), proof);
+        assert!(zkp.verify_noninteractive("alice", &statement, &proof, b"context"));
+        let out_of_range = format!("\"{}\"", "ff".repeat(56));
+        let err = Proof::<ModPGroup>::decoder(&zkp.group).deserialize(&mut serde_json::Deserializer::from_str(&out_of_range));
+        assert!(err.unwrap_err().to_string().starts_with("scalar is not below q"));
+
+        let (alpha, beta) = RistrettoGroup::generators();
+        let zkp = ZKP::new(RistrettoGroup::new(), alpha, beta);
+        let statement = zkp.statement(&zkp.group.random_scalar());
+        assert_eq!(statement.to_bytes(&zkp.group).len(), 64);
+        let json = serde_json::to_string(&statement.encoded(&zkp.group)).unwrap();
+        let decoded = Statement::decoder(&zkp.group).deserialize(&mut serde_json::Deserializer::from_str(&json));
+        assert_eq!(decoded.unwrap(), statement);
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let zkp = params::RFC5114_1024_160.zkp().unwrap();
+        let mut proofs: Vec<_> = (0..8)
+            .map(|_| {
+                let x = zkp.group.random_scalar();
+                let k = zkp.group.random_scalar();
+                let c = zkp.group.random_scalar();
+                let s = zkp.solve(&k, &c, &x);
+                (zkp.statement(&x), zkp.commitment(&k), Proof { c, s })
+            })
+            .collect();
+        assert_eq!(zkp.verify_batch(&proofs), Ok(()));
+        assert_eq!(zkp.verify_batch(&[]), Ok(()));
+
+        proofs[2].2.s = zkp.group.scalar_add(&proofs[2].2.s, &BigUint::from(1u32));
+        proofs[5].1 = zkp.commitment(&zkp.group.random_scalar());
+        assert_eq!(zkp.verify_batch(&proofs), Err(vec![2, 5]));
+
+        let (alpha, beta) = RistrettoGroup::generators();
+        let zkp = ZKP::new(RistrettoGroup::new(), alpha, beta);
+        let mut proofs: Vec<_> = (0..8)
+            .map(|_| {
+                let x = zkp.group.random_scalar();
+                let k = zkp.group.random_scalar();
+                let c = zkp.group.random_scalar();
+                let s = zkp.solve(&k, &c, &x);
+                (zkp.statement(&x), zkp.commitment(&k), Proof { c, s })
+            })
+            .collect();
+        assert_eq!(zkp.verify_batch(&proofs), Ok(()));
+
+        proofs[7].0 = zkp.statement(&zkp.group.random_scalar());
+        assert_eq!(zkp.verify_batch(&proofs), Err(vec![7]));
+    }
+
+    #[test]
+    fn test_multi_exp() {
+        let group = params::RFC5114_1024_160.group().unwrap();
+        let terms: Vec<_> = (0..5)
+            .map(|_| (generate_random_below(&group.p), group.random_scalar()))
+            .collect();
+        let expected = terms.iter().fold(BigUint::from(1u32), |acc, (base, exponent)| group.op(&acc, &group.exp(base, exponent)));
+        assert_eq!(group.multi_exp(&terms), expected);
+        assert_eq!(group.multi_exp(&[]), BigUint::from(1u32));
+    }
+
+    #[test]
+    fn test_fixed_base_tables() {
+        let set = params::RFC5114_2048_224;
+        let group = set.group().unwrap();
+        let alpha = set.alpha();
+        let table = group.precompute(&alpha);
+        for x in [BigUint::from(0u32), BigUint::from(1u32), group.q.clone() - 1u32, group.random_scalar()] {
+            assert_eq!(group.exp_fixed(&table, &x), group.exp(&alpha, &x));
+        }
+
+        let y = group.exp(&set.beta().unwrap(), &group.random_scalar());
+        let (x1, x2) = (group.random_scalar(), group.random_scalar());
+        assert_eq!(group.exp2(&alpha, &table, &x1, &y, &x2), group.op(&group.exp(&alpha, &x1), &group.exp(&y, &x2)));
+
+        let ristretto = RistrettoGroup::new();
+        let (alpha, beta) = RistrettoGroup::generators();
+        let table = ristretto.precompute(&beta);
+        let (x1, x2) = (ristretto.random_scalar(), ristretto.random_scalar());
+        assert_eq!(ristretto.exp_fixed(&table, &x1), ristretto.exp(&beta, &x1));
+        assert_eq!(ristretto.exp2(&beta, &table, &x1, &alpha, &x2), ristretto.op(&ristretto.exp(&beta, &x1), &ristretto.exp(&alpha, &x2)));
+    }
+
+    #[test]
+    fn test_constant_time_scalar_arithmetic() {
+        for group in [ModPGroup::new(BigUint::from(23u32), BigUint::from(11u32)).unwrap(), params::RFC5114_2048_256.group().unwrap()] {
+            let q = &group.q;
+            for _ in 0..10 {
+                let (a, b) = (group.random_scalar(), group.random_scalar());
+                assert_eq!(group.scalar_mul(&a, &b), (&a * &b) % q);
+                assert_eq!(group.scalar_add(&a, &b), (&a + &b) % q);
+                assert_eq!(group.scalar_sub(&a, &b), (&a + q - &b) % q);
+                assert_eq!(group.scalar_neg(&a), (q - &a) % q);
+            }
+            let digest = BigUint::from_bytes_be(&<sha2::Sha512 as sha2::Digest>::digest(b"password"));
+            assert_eq!(group.hash_to_scalar(b"password"), digest % q);
+        }
+    }
+
+    #[test]
+    fn test_derive_secret() {
+        // cheap parameters, the cost does not change the reduction
+        let kdf = KdfParams { memory: 64, iterations: 1, parallelism: 1 };
+        let set = params::RFC5114_2048_256;
+        let zkp = set.zkp().unwrap();
+        let group = &zkp.group;
+        let salt = generate_salt();
+
+        let x = derive_secret(group, b"password", &salt, &kdf).unwrap();
+        assert!(x < group.q);
+        assert_eq!(derive_secret(group, b"password", &salt, &kdf).unwrap(), x);
+        assert_ne!(derive_secret(group, b"password", &generate_salt(), &kdf).unwrap(), x);
+        assert_ne!(derive_secret(group, b"passwore", &salt, &kdf).unwrap(), x);
+        assert_eq!(derive_secret(group, b"password", b"short", &kdf), Err(KdfError::SaltTooShort));
+
+        let statement = zkp.statement(&x);
+        let proof = zkp.prove_noninteractive("alice", &derive_secret(group, b"password", &salt, &kdf).unwrap(), b"");
+        assert!(zkp.verify_noninteractive("alice", &statement, &proof, b""));
+
+        let wide: Vec<u8> = (0..group.wide_scalar_len()).map(|i| i as u8 ^ 0xa5).collect();
+        assert_eq!(group.scalar_from_wide_bytes(&wide), BigUint::from_bytes_be(&wide) % &group.q);
+
+        let ristretto = RistrettoGroup::new();
+        let x = derive_secret(&ristretto, b"password", &salt, &kdf).unwrap();
+        assert_eq!(derive_secret(&ristretto, b"password", &salt, &kdf).unwrap(), x);
+    }
+
+    #[test]
+    fn test_nonce_strategies() {
+        let zkp = params::RFC5114_2048_224.zkp().unwrap();
+        let x = zkp.group.random_scalar();
+        let y = zkp.group.random_scalar();
+
+        let k = zkp.nonce(NonceStrategy::Deterministic, &x, b"context");
+        assert!(k < zkp.group.q);
+        assert_eq!(zkp.nonce(NonceStrategy::Deterministic, &x, b"context"), k);
+        assert_ne!(zkp.nonce(NonceStrategy::Deterministic, &y, b"context"), k);
+        assert_ne!(zkp.nonce(NonceStrategy::Hedged, &x, b"context"), k);
+        assert_ne!(zkp.nonce(NonceStrategy::Hedged, &x, b"context"), zkp.nonce(NonceStrategy::Hedged, &x, b"context"));
+
+        // distinct contexts never share a nonce
+        for strategy in [NonceStrategy::Random, NonceStrategy::Deterministic, NonceStrategy::Hedged] {
+            let nonces: std::collections::HashSet<_> = (0..100u32)
+                .map(|i| zkp.nonce(strategy, &x, &i.to_be_bytes()))
+                .collect();
+            assert_eq!(nonces.len(), 100);
+        }
+
+        // a deterministic Fiat-Shamir proof is reproducible and only depends on the context
+        let statement = zkp.statement(&x);
+        let proof = zkp.prove_noninteractive_with("alice", &x, b"login/1", NonceStrategy::Deterministic);
+        assert_eq!(zkp.prove_noninteractive_with("alice", &x, b"login/1", NonceStrategy::Deterministic), proof);
+        assert!(zkp.verify_noninteractive("alice", &statement, &proof, b"login/1"));
+        let other = zkp.prove_noninteractive_with("alice", &x, b"login/2", NonceStrategy::Deterministic);
+        assert_ne!(other.s, proof.s);
+        assert!(zkp.verify_noninteractive("alice", &statement, &other, b"login/2"));
+
+        let ristretto = {
+            let (alpha, beta) = RistrettoGroup::generators();
+            ZKP::new(RistrettoGroup::new(), alpha, beta)
         };
+        let x = ristretto.group.random_scalar();
+        let proof = ristretto.prove_noninteractive_with("alice", &x, b"login/1", NonceStrategy::Hedged);
+        assert!(ristretto.verify_noninteractive("alice", &ristretto.statement(&x), &proof, b"login/1"));
+        assert_eq!(ristretto.nonce(NonceStrategy::Deterministic, &x, b"a"), ristretto.nonce(NonceStrategy::Deterministic, &x, b"a"));
+    }
+
+    #[test]
+    fn test_decode_checked() {
+        let group = ModPGroup::new(BigUint::from(23u32), BigUint::from(11u32)).unwrap();
+        assert_eq!(group.decode_checked(&[4]), Ok(BigUint::from(4u32)));
+        assert_eq!(group.decode_checked(&[0]), Err(EncodingError::NotInSubgroup));
+        assert_eq!(group.decode_checked(&[1]), Err(EncodingError::Identity));
+        // 5 generates all of Z_23^*, its order is 22
+        assert_eq!(group.decode_checked(&[5]), Err(EncodingError::NotInSubgroup));
+        assert_eq!(group.decode_checked(&[22]), Err(EncodingError::NotInSubgroup));
+        assert_eq!(group.decode_checked(&[23]), Err(EncodingError::InvalidElement));
+        assert_eq!(group.decode_checked(&[0, 4]), Err(EncodingError::InvalidLength { expected: 1, actual: 2 }));
+        assert_eq!(group.decode_scalar_checked(&[10]), Ok(BigUint::from(10u32)));
+        assert_eq!(group.decode_scalar_checked(&[11]), Err(EncodingError::InvalidScalar));
+        assert_eq!(group.decode_scalar_checked(&[]), Err(EncodingError::InvalidLength { expected: 1, actual: 0 }));
+
+        let zkp = params::RFC5114_2048_256.zkp().unwrap();
+        let group = &zkp.group;
+        let y = zkp.statement(&group.random_scalar()).y1;
+        assert_eq!(group.decode_checked(&group.encode_element(&y)), Ok(y));
+        let minus_one = &group.p - 1u32;
+        assert_eq!(group.decode_checked(&group.encode_element(&minus_one)), Err(EncodingError::NotInSubgroup));
+        assert_eq!(group.decode_checked(&group.p.to_bytes_be()), Err(EncodingError::InvalidElement));
+
+        let ristretto = RistrettoGroup::new();
+        let point = ristretto.hash_to_element(b"point");
+        assert_eq!(ristretto.decode_checked(&ristretto.encode_element(&point)), Ok(point));
+        assert_eq!(ristretto.decode_checked(&ristretto.encode_element(&ristretto.identity())), Err(EncodingError::Identity));
+        assert_eq!(ristretto.decode_checked(&[0xff; 32]), Err(EncodingError::InvalidElement));
+        assert_eq!(ristretto.decode_checked(&[0; 31]), Err(EncodingError::InvalidLength { expected: 32, actual: 31 }));
+        assert_eq!(ristretto.decode_scalar_checked(&[0xff; 32]), Err(EncodingError::InvalidScalar));
+    }
 
-        let x = ZKP::generate_random_below(&q);
-        let k = ZKP::generate_random_below(&q);
+    fn check_store(store: &dyn store::Store) {
+        use store::{PendingChallenge, Session, UserInfo};
+
+        assert_eq!(store.get_user("alice").unwrap(), None);
+        let alice = UserInfo { user_name: "alice".to_string(), y1: vec![1], y2: vec![2], salt: vec![3; 16] };
+        assert!(store.insert_user(&alice).unwrap());
+        assert_eq!(store.get_user("alice").unwrap(), Some(alice.clone()));
+
+        // registering a taken name changes nothing
+        let mallory = UserInfo { y1: vec![6], ..alice.clone() };
+        assert!(!store.insert_user(&mallory).unwrap());
+        assert_eq!(store.get_user("alice").unwrap(), Some(alice.clone()));
+
+        // rotation only applies on top of the record it was authorized against
+        let rotated = UserInfo { y1: vec![7], y2: vec![8], salt: vec![4; 16], ..alice.clone() };
+        assert!(store.replace_user(&alice, &rotated).unwrap());
+        assert!(!store.replace_user(&alice, &mallory).unwrap());
+        assert_eq!(store.get_user("alice").unwrap(), Some(rotated));
+        assert!(!store.replace_user(&UserInfo::default(), &UserInfo::default()).unwrap());
+
+        // two devices logging in at once each keep their own challenge, each can be taken once
+        assert_eq!(store.take_challenge("auth").unwrap(), None);
+        let first = PendingChallenge { auth_id: "auth".to_string(), user: "alice".to_string(), r1: vec![5], r2: vec![6], c: vec![7], created_at: 10 };
+        let second = PendingChallenge { auth_id: "auth2".to_string(), r1: vec![8], c: vec![9], created_at: 20, ..first.clone() };
+        store.put_challenge(&first).unwrap();
+        store.put_challenge(&second).unwrap();
+        assert_eq!(store.take_challenge("auth").unwrap(), Some(first.clone()));
+        assert_eq!(store.take_challenge("auth").unwrap(), None);
+        assert_eq!(store.take_challenge("auth2").unwrap(), Some(second.clone()));
+
+        store.put_challenge(&first).unwrap();
+        store.put_challenge(&second).unwrap();
+        assert_eq!(store.remove_expired_challenges(15).unwrap(), 1);
+        assert_eq!(store.take_challenge("auth").unwrap(), None);
+        assert_eq!(store.remove_expired_challenges(15).unwrap(), 0);
+        store.put_challenge(&first).unwrap();
+
+        assert_eq!(store.get_session("session").unwrap(), None);
+        let session = Session { session_id: "session".to_string(), user: "alice".to_string(), created_at: 10, expires_at: 100 };
+        let other = Session { session_id: "other".to_string(), expires_at: 50, ..session.clone() };
+        store.put_session(&session).unwrap();
+        store.put_session(&other).unwrap();
+        assert_eq!(store.get_session("session").unwrap(), Some(session.clone()));
+        assert!(store.remove_session("other").unwrap());
+        assert!(!store.remove_session("other").unwrap());
+        store.put_session(&other).unwrap();
+        assert_eq!(store.remove_expired_sessions(60).unwrap(), 1);
+        assert_eq!(store.get_session("other").unwrap(), None);
+        assert_eq!(store.get_session("session").unwrap(), Some(session.clone()));
+
+        // revoking and deleting only touch the records of that user
+        let bob = UserInfo { user_name: "bob".to_string(), ..alice };
+        let bob_session = Session { session_id: "bob".to_string(), user: "bob".to_string(), ..session.clone() };
+        let bob_challenge = PendingChallenge { auth_id: "bob".to_string(), user: "bob".to_string(), ..first.clone() };
+        assert!(store.insert_user(&bob).unwrap());
+        store.put_session(&bob_session).unwrap();
+        store.put_session(&other).unwrap();
+        assert_eq!(store.remove_user_sessions("alice").unwrap(), 2);
+        assert_eq!(store.get_session("bob").unwrap(), Some(bob_session.clone()));
+        store.put_session(&other).unwrap();
+        store.put_challenge(&bob_challenge).unwrap();
+        assert!(store.delete_account("bob").unwrap());
+        assert!(!store.delete_account("bob").unwrap());
+        assert_eq!(store.get_user("bob").unwrap(), None);
+        assert_eq!(store.get_session("bob").unwrap(), None);
+        assert_eq!(store.take_challenge("bob").unwrap(), None);
+        assert_eq!(store.get_session("other").unwrap(), Some(other));
+        store.put_session(&session).unwrap();
+
+        // a proof is accepted once until it expires, the same challenge of another user is not a replay
+        assert!(store.insert_proof("alice", &[1], 100).unwrap());
+        assert!(!store.insert_proof("alice", &[1], 100).unwrap());
+        assert!(store.insert_proof("bob", &[1], 100).unwrap());
+        assert!(store.insert_proof("alice", &[2], 50).unwrap());
+        assert!(store.has_proof("alice", &[2]).unwrap());
+        assert_eq!(store.remove_expired_proofs(60).unwrap(), 1);
+        assert!(!store.has_proof("alice", &[2]).unwrap());
+        assert!(!store.insert_proof("alice", &[1], 100).unwrap());
+        assert!(store.insert_proof("alice", &[2], 50).unwrap());
+    }
 
-        let c = ZKP::generate_random_below(&q);
+    #[test]
+    fn test_stores() {
+        use store::{ChallengeStore, ProofStore, SessionStore, UserStore};
+
+        check_store(&store::MemoryStore::default());
+        check_store(&store::SqliteStore::open_in_memory().unwrap());
+
+        // a file-backed store keeps its data across reopening
+        let path = std::env::temp_dir().join(format!("zkp-store-{}.sqlite", generate_random_string(8)));
+        {
+            let sqlite = store::SqliteStore::open(&path).unwrap();
+            check_store(&sqlite);
+            assert_eq!(sqlite.version().unwrap(), 6);
+            sqlite.bind_group("ristretto255").unwrap();
+        }
+        let sqlite = store::SqliteStore::open(&path).unwrap();
+        assert_eq!(sqlite.take_challenge("auth").unwrap().unwrap().c, vec![7]);
+        assert_eq!(sqlite.take_challenge("auth2").unwrap().unwrap().c, vec![9]);
+        assert_eq!(sqlite.get_session("session").unwrap().unwrap().expires_at, 100);
+        assert_eq!(sqlite.get_user("alice").unwrap().unwrap().salt, vec![4; 16]);
+        assert!(!sqlite.insert_proof("alice", &[1], 100).unwrap());
+
+        // the keys in it are only usable in the group they were registered in
+        sqlite.bind_group("ristretto255").unwrap();
+        assert!(matches!(sqlite.bind_group("ffdhe2048"), Err(store::StoreError::GroupMismatch { .. })));
+        drop(sqlite);
+        assert!(store::open(Some(path.as_path()), "ristretto255").is_ok());
+        assert!(matches!(store::open(Some(path.as_path()), "ffdhe2048"), Err(store::StoreError::GroupMismatch { .. })));
+        let sqlite = store::SqliteStore::open(&path).unwrap();
+
+        // databases from a newer version are refused instead of being misread
+        rusqlite::Connection::open(&path).unwrap().pragma_update(None, "user_version", 99).unwrap();
+        drop(sqlite);
+        assert!(matches!(store::SqliteStore::open(&path), Err(store::StoreError::UnsupportedSchema(99))));
+        std::fs::remove_file(&path).unwrap();
+    }
 
+    #[test]
+    fn test_session_tokens() {
+        use base64::Engine;
+        use token::{Claims, TokenSigner};
+
+        let new_key = || ring::signature::Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        let der = new_key();
+        let pem = pem::encode(&pem::Pem::new("PRIVATE KEY", der.as_ref()));
+        let signer = TokenSigner::from_pem(pem.as_bytes()).unwrap();
+        assert_eq!(TokenSigner::from_pkcs8_der(der.as_ref()).unwrap().public_key(), signer.public_key());
+
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let claims = Claims { sub: "alice".to_string(), iat: now, exp: now + 60, auth_id: Some("auth".to_string()) };
+        let token = signer.sign(&claims).unwrap();
+        let key = signer.public_key();
+        assert_eq!((key.kty.as_str(), key.crv.as_str(), key.alg.as_str()), ("OKP", "Ed25519", "EdDSA"));
+        assert_eq!(key.verify(&token).unwrap(), claims);
+        assert_eq!(jsonwebtoken::decode_header(&token).unwrap().kid.as_ref(), Some(&key.kid));
+
+        // tampered, expired and foreign tokens are rejected
+        let (head, signature) = token.rsplit_once('.').unwrap();
+        let forged = Claims { sub: "mallory".to_string(), ..claims.clone() };
+        let forged_payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap());
+        let header = head.split('.').next().unwrap();
+        assert!(key.verify(&format!("{header}.{forged_payload}.{signature}")).is_err());
+        let expired = Claims { iat: now - 120, exp: now - 60, auth_id: None, ..claims.clone() };
+        assert!(key.verify(&signer.sign(&expired).unwrap()).is_err());
+        let other = TokenSigner::from_pkcs8_der(new_key().as_ref()).unwrap();
+        assert_ne!(other.public_key().kid, key.kid);
+        assert!(other.public_key().verify(&token).is_err());
+
+        assert!(TokenSigner::from_pem(b"not a key").is_err());
+    }
 
+    #[test]
+    fn test_rotation_proof() {
+        let zkp = params::RFC5114_2048_224.zkp().unwrap();
+        let (old_x, new_x) = (zkp.group.random_scalar(), zkp.group.random_scalar());
+        let old = zkp.statement(&old_x);
+        let new = zkp.statement(&new_x);
+        let (y1, y2) = (zkp.group.encode_element(&new.y1), zkp.group.encode_element(&new.y2));
+        let salt = [1u8; 16];
+
+        let context = rotate_context(100, &y1, &y2, &salt);
+        let proof = zkp.prove_noninteractive("alice", &old_x, &context);
+        assert!(zkp.verify_noninteractive("alice", &old, &proof, &context));
+        // the proof only authorizes the keys it was made for
+        assert!(!zkp.verify_noninteractive("alice", &old, &proof, &rotate_context(100, &y2, &y1, &salt)));
+        assert!(!zkp.verify_noninteractive("alice", &old, &proof, &rotate_context(100, &y1, &y2, &[2u8; 16])));
+        assert!(!zkp.verify_noninteractive("alice", &old, &proof, &rotate_context(101, &y1, &y2, &salt)));
+        assert!(!zkp.verify_noninteractive("alice", &old, &proof, &login_context(100)));
+        // a proof for the new keys is no use
+        assert!(!zkp.verify_noninteractive("alice", &old, &zkp.prove_noninteractive("alice", &new_x, &context), &context));
+    }
 
+    #[test]
+    fn test_account_proofs() {
+        let (alpha, beta) = RistrettoGroup::generators();
+        let zkp = ZKP::new(RistrettoGroup::new(), alpha, beta);
+        let x = zkp.group.random_scalar();
+        let statement = zkp.statement(&x);
+
+        // a proof only authorizes the action it was made for
+        let proof = zkp.prove_noninteractive("alice", &x, &delete_context(100));
+        assert!(zkp.verify_noninteractive("alice", &statement, &proof, &delete_context(100)));
+        assert!(!zkp.verify_noninteractive("alice", &statement, &proof, &revoke_context(100)));
+        assert!(!zkp.verify_noninteractive("alice", &statement, &proof, &login_context(100)));
+        let login = zkp.prove_noninteractive("alice", &x, &login_context(100));
+        assert!(!zkp.verify_noninteractive("alice", &statement, &login, &delete_context(100)));
     }
-}
\ No newline at end of file
+}
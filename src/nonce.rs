@@ -0,0 +1,60 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+use crate::group::Group;
+
+// Domain separation tag of the nonce derivation
+const NONCE_TAG: &[u8] = b"zkp-grpc/chaum-pedersen/nonce";
+
+// How the prover picks the nonce k. Two responses s = k - c * x and s' = k - c' * x
+// with the same k and different challenges reveal x, so k must never repeat
+// across proofs that can be answered to different challenges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonceStrategy {
+    // k drawn from the thread rng, only as good as the rng
+    #[default]
+    Random,
+    // k = HMAC-SHA256(x, context) expanded and reduced mod q, in the spirit of RFC 6979.
+    // Only safe when a context is never reused with a different challenge, as with
+    // Fiat-Shamir where the challenge is itself a function of the context.
+    Deterministic,
+    // Deterministic with 32 fresh random bytes mixed in: a broken rng degrades to
+    // the deterministic derivation instead of leaking x
+    Hedged,
+}
+
+impl NonceStrategy {
+    pub fn nonce<G: Group>(&self, group: &G, x: &G::Scalar, context: &[u8]) -> G::Scalar {
+        let randomness = match self {
+            NonceStrategy::Random => return group.random_scalar(),
+            NonceStrategy::Deterministic => Vec::new(),
+            NonceStrategy::Hedged => {
+                let mut bytes = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut bytes);
+                bytes
+            }
+        };
+
+        // HMAC(x, tag || block || randomness || context) for block = 0, 1, ... until
+        // there are enough bytes for a uniform reduction mod q
+        let key = group.encode_scalar(x);
+        let len = group.wide_scalar_len();
+        let mut wide = Vec::with_capacity(len + 32);
+        for block in 0u32.. {
+            if wide.len() >= len {
+                break;
+            }
+            let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts keys of any length");
+            mac.update(NONCE_TAG);
+            mac.update(&block.to_be_bytes());
+            for part in [&randomness[..], context] {
+                mac.update(&(part.len() as u32).to_be_bytes());
+                mac.update(part);
+            }
+            wide.extend_from_slice(&mac.finalize().into_bytes());
+        }
+        wide.truncate(len);
+        group.scalar_from_wide_bytes(&wide)
+    }
+}
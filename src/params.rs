@@ -0,0 +1,222 @@
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+
+use crate::group::{verify_generator, Group, ModPGroup, MAX_MODULUS_BITS};
+use crate::ZKP;
+
+// Number of Miller-Rabin rounds used when validating p and q
+const PRIMALITY_ROUNDS: usize = 24;
+
+// A named discrete-log parameter set: a prime p, the prime order q of the
+// subgroup we work in, and a generator alpha of that subgroup. Values are hex.
+// The second generator beta is hashed into the group from `beta_seed()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParameterSet {
+    pub name: &'static str,
+    p: &'static str,
+    q: &'static str,
+    alpha: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterError {
+    UnknownName(String),
+    InvalidHex(&'static str),
+    NotPrime(&'static str),
+    TooLarge(&'static str),
+    Even(&'static str),
+    OrderDoesNotDivide,
+    NotAGenerator(&'static str),
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::UnknownName(name) => write!(f, "unknown parameter set {name}"),
+            ParameterError::InvalidHex(value) => write!(f, "{value} is not a valid hex number"),
+            ParameterError::NotPrime(value) => write!(f, "{value} is not prime"),
+            ParameterError::TooLarge(value) => write!(f, "{value} is wider than {MAX_MODULUS_BITS} bits"),
+            ParameterError::Even(value) => write!(f, "{value} is even"),
+            ParameterError::OrderDoesNotDivide => write!(f, "q does not divide p - 1"),
+            ParameterError::NotAGenerator(value) => write!(f, "{value} does not generate the order q subgroup"),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+// RFC 5114 section 2.1: 1024-bit MODP group with 160-bit prime order subgroup
+pub const RFC5114_1024_160: ParameterSet = ParameterSet::new(
+    "rfc5114-1024-160",
+    "B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371",
+    "F518AA8781A8DF278ABA4E7D64B7CB9D49462353",
+    "A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5",
+);
+
+// RFC 5114 section 2.2: 2048-bit MODP group with 224-bit prime order subgroup
+pub const RFC5114_2048_224: ParameterSet = ParameterSet::new(
+    "rfc5114-2048-224",
+    "AD107E1E9123A9D0D660FAA79559C51FA20D64E5683B9FD1B54B1597B61D0A75E6FA141DF95A56DBAF9A3C407BA1DF15EB3D688A309C180E1DE6B85A1274A0A66D3F8152AD6AC2129037C9EDEFDA4DF8D91E8FEF55B7394B7AD5B7D0B6C12207C9F98D11ED34DBF6C6BA0B2C8BBC27BE6A00E0A0B9C49708B3BF8A317091883681286130BC8985DB1602E714415D9330278273C7DE31EFDC7310F7121FD5A07415987D9ADC0A486DCDF93ACC44328387315D75E198C641A480CD86A1B9E587E8BE60E69CC928B2B9C52172E413042E9B23F10B0E16E79763C9B53DCF4BA80A29E3FB73C16B8E75B97EF363E2FFA31F71CF9DE5384E71B81C0AC4DFFE0C10E64F",
+    "801C0D34C58D93FE997177101F80535A4738CEBCBF389A99B36371EB",
+    "AC4032EF4F2D9AE39DF30B5C8FFDAC506CDEBE7B89998CAF74866A08CFE4FFE3A6824A4E10B9A6F0DD921F01A70C4AFAAB739D7700C29F52C57DB17C620A8652BE5E9001A8D66AD7C17669101999024AF4D027275AC1348BB8A762D0521BC98AE247150422EA1ED409939D54DA7460CDB5F6C6B250717CBEF180EB34118E98D119529A45D6F834566E3025E316A330EFBB77A86F0C1AB15B051AE3D428C8F8ACB70A8137150B8EEB10E183EDD19963DDD9E263E4770589EF6AA21E7F5F2FF381B539CCE3409D13CD566AFBB48D6C019181E1BCFE94B30269EDFE72FE9B6AA4BD7B5A0F1C71CFFF4C19C418E1F6EC017981BC087F2A7065B384B890D3191F2BFA",
+);
+
+// RFC 5114 section 2.3: 2048-bit MODP group with 256-bit prime order subgroup
+pub const RFC5114_2048_256: ParameterSet = ParameterSet::new(
+    "rfc5114-2048-256",
+    "87A8E61DB4B6663CFFBBD19C651959998CEEF608660DD0F25D2CEED4435E3B00E00DF8F1D61957D4FAF7DF4561B2AA3016C3D91134096FAA3BF4296D830E9A7C209E0C6497517ABD5A8A9D306BCF67ED91F9E6725B4758C022E0B1EF4275BF7B6C5BFC11D45F9088B941F54EB1E59BB8BC39A0BF12307F5C4FDB70C581B23F76B63ACAE1CAA6B7902D52526735488A0EF13C6D9A51BFA4AB3AD8347796524D8EF6A167B5A41825D967E144E5140564251CCACB83E6B486F6B3CA3F7971506026C0B857F689962856DED4010ABD0BE621C3A3960A54E710C375F26375D7014103A4B54330C198AF126116D2276E11715F693877FAD7EF09CADB094AE91E1A1597",
+    "8CF83642A709A097B447997640129DA299B1A47D1EB3750BA308B0FE64F5FBD3",
+    "3FB32C9B73134D0B2E77506660EDBD484CA7B18F21EF205407F4793A1A0BA12510DBC15077BE463FFF4FED4AAC0BB555BE3A6C1B0C6B47B1BC3773BF7E8C6F62901228F8C28CBB18A55AE31341000A650196F931C77A57F2DDF463E5E9EC144B777DE62AAAB8A8628AC376D282D6ED3864E67982428EBC831D14348F6F2F9193B5045AF2767164E1DFC967C1FB3F2E55A4BD1BFFE83B9C80D052B985D182EA0ADB2A3B7313D3FE14C8484B1E052588B9B7D2BBD2DF016199ECD06E1557CD0915B3353BBB64E0EC377FD028370DF92B52C7891428CDC67EB6184B523D1DB246C32F63078490F00EF8D647D148D47954515E2327CFEF98C582664B4C0F6CC41659",
+);
+
+// RFC 7919 appendix A.1: 2048-bit safe prime group
+pub const FFDHE2048: ParameterSet = ParameterSet::new(
+    "ffdhe2048",
+    "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617AD3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797ABC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F619172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005C58EF1837D1683B2C6F34A26C1B2EFFA886B423861285C97FFFFFFFFFFFFFFFF",
+    "7FFFFFFFFFFFFFFFD6FC2A2C515DA54D57EE2B10139E9E78EC5CE2C1E7169B4AD4F09B208A3219FDE649CEE7124D9F7CBE97F1B1B1863AEC7B40D901576230BD69EF8F6AEAFEB2B09219FA8FAF83376842B1B2AA9EF68D79DAAB89AF3FABE49ACC278638707345BBF15344ED79F7F4390EF8AC509B56F39A98566527A41D3CBD5E0558C159927DB0E88454A5D96471FDDCB56D5BB06BFA340EA7A151EF1CA6FA572B76F3B1B95D8C8583D3E4770536B84F017E70E6FBF176601A0266941A17B0C8B97F4E74C2C1FFC7278919777940C1E1FF1D8DA637D6B99DDAFE5E17611002E2C778C1BE8B41D96379A51360D977FD4435A11C30942E4BFFFFFFFFFFFFFFFF",
+    "02",
+);
+
+// RFC 7919 appendix A.2: 3072-bit safe prime group
+pub const FFDHE3072: ParameterSet = ParameterSet::new(
+    "ffdhe3072",
+    "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617AD3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797ABC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F619172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035BBC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91CAEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B66C62E37FFFFFFFFFFFFFFFF",
+    "7FFFFFFFFFFFFFFFD6FC2A2C515DA54D57EE2B10139E9E78EC5CE2C1E7169B4AD4F09B208A3219FDE649CEE7124D9F7CBE97F1B1B1863AEC7B40D901576230BD69EF8F6AEAFEB2B09219FA8FAF83376842B1B2AA9EF68D79DAAB89AF3FABE49ACC278638707345BBF15344ED79F7F4390EF8AC509B56F39A98566527A41D3CBD5E0558C159927DB0E88454A5D96471FDDCB56D5BB06BFA340EA7A151EF1CA6FA572B76F3B1B95D8C8583D3E4770536B84F017E70E6FBF176601A0266941A17B0C8B97F4E74C2C1FFC7278919777940C1E1FF1D8DA637D6B99DDAFE5E17611002E2C778C1BE8B41D96379A51360D977FD4435A11C308FE7EE6F1AAD9DB28C81ADDE1A7A6F7CCE011C30DA37E4EB736483BD6C8E9348FBFBF72CC6587D60C36C8E577F0984C289C9385A098649DE21BCA27A7EA229716BA6E9B279710F38FAA5FFAE574155CE4EFB4F743695E2911B1D06D5E290CBCD86F56D0EDFCD216AE22427055E6835FD29EEF79E0D90771FEACEBE12F20E95B363171BFFFFFFFFFFFFFFFF",
+    "02",
+);
+
+pub const PARAMETER_SETS: &[ParameterSet] = &[
+    RFC5114_1024_160,
+    RFC5114_2048_224,
+    RFC5114_2048_256,
+    FFDHE2048,
+    FFDHE3072,
+];
+
+pub const DEFAULT_PARAMETER_SET: ParameterSet = RFC5114_2048_256;
+
+impl ParameterSet {
+    pub const fn new(name: &'static str, p: &'static str, q: &'static str, alpha: &'static str) -> Self {
+        ParameterSet { name, p, q, alpha }
+    }
+
+    pub fn by_name(name: &str) -> Result<ParameterSet, ParameterError> {
+        PARAMETER_SETS
+            .iter()
+            .find(|set| set.name == name)
+            .copied()
+            .ok_or_else(|| ParameterError::UnknownName(name.to_string()))
+    }
+
+    pub fn p(&self) -> BigUint {
+        parse_hex(self.p).expect("Invalid hex string")
+    }
+
+    pub fn q(&self) -> BigUint {
+        parse_hex(self.q).expect("Invalid hex string")
+    }
+
+    pub fn alpha(&self) -> BigUint {
+        parse_hex(self.alpha).expect("Invalid hex string")
+    }
+
+    // Public seed from which beta is derived
+    pub fn beta_seed(&self) -> String {
+        format!("zkp-grpc/{}/beta", self.name)
+    }
+
+    pub fn beta(&self) -> Result<BigUint, ParameterError> {
+        Ok(self.group()?.hash_to_element(self.beta_seed().as_bytes()))
+    }
+
+    // Lets auditors check that a published beta really is the hash of the seed
+    pub fn verify_beta(&self, beta: &BigUint) -> bool {
+        self.group().is_ok_and(|group| verify_generator(&group, self.beta_seed().as_bytes(), beta))
+    }
+
+    pub fn group(&self) -> Result<ModPGroup, ParameterError> {
+        let p = parse_hex(self.p).ok_or(ParameterError::InvalidHex("p"))?;
+        let q = parse_hex(self.q).ok_or(ParameterError::InvalidHex("q"))?;
+        ModPGroup::new(p, q)
+    }
+
+    // Validates the set and builds the protocol instance for it
+    pub fn zkp(&self) -> Result<ZKP<ModPGroup>, ParameterError> {
+        self.validate()?;
+        Ok(ZKP::new(self.group()?, self.alpha(), self.beta()?))
+    }
+
+    // Checks that p and q are odd primes that fit the group, q | p - 1 and that
+    // alpha and beta are non-trivial elements of the order q subgroup
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        // the sizes and parities the group needs come first, it cannot be built otherwise
+        let group = self.group()?;
+        let (p, q) = (&group.p, &group.q);
+        let alpha = parse_hex(self.alpha).ok_or(ParameterError::InvalidHex("alpha"))?;
+
+        if !is_probable_prime(p, PRIMALITY_ROUNDS) {
+            return Err(ParameterError::NotPrime("p"));
+        }
+        if !is_probable_prime(q, PRIMALITY_ROUNDS) {
+            return Err(ParameterError::NotPrime("q"));
+        }
+
+        let one = BigUint::from(1u32);
+        if (p - &one) % q != BigUint::ZERO {
+            return Err(ParameterError::OrderDoesNotDivide);
+        }
+
+        let beta = group.hash_to_element(self.beta_seed().as_bytes());
+        for (name, generator) in [("alpha", &alpha), ("beta", &beta)] {
+            if *generator <= one || *generator >= *p || generator.modpow(q, p) != one {
+                return Err(ParameterError::NotAGenerator(name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_hex(value: &str) -> Option<BigUint> {
+    BigUint::parse_bytes(value.as_bytes(), 16)
+}
+
+// Miller-Rabin with random bases
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> bool {
+    let one = BigUint::from(1u32);
+    let two = BigUint::from(2u32);
+    let three = BigUint::from(3u32);
+
+    if *n < two {
+        return false;
+    }
+    if *n <= three {
+        return true;
+    }
+    if !n.bit(0) {
+        return false;
+    }
+
+    // n - 1 = d * 2^r with d odd
+    let n_minus_one = n - &one;
+    let r = n_minus_one.trailing_zeros().expect("n - 1 is non-zero");
+    let d = &n_minus_one >> r;
+
+    let mut rng = rand::thread_rng();
+    'witness: for _ in 0..rounds {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..r {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::{self, DeserializeSeed, Visitor};
+use serde::{Deserializer, Serialize, Serializer};
+
+use crate::group::Group;
+
+// What the prover registers: y1 = alpha^x, y2 = beta^x
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement<G: Group> {
+    pub y1: G::Element,
+    pub y2: G::Element,
+}
+
+// First message of the prover: r1 = alpha^k, r2 = beta^k
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commitment<G: Group> {
+    pub r1: G::Element,
+    pub r2: G::Element,
+}
+
+// Challenge and response: s = k - c * x mod q
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proof<G: Group> {
+    pub c: G::Scalar,
+    pub s: G::Scalar,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodingError {
+    InvalidLength { expected: usize, actual: usize },
+    InvalidElement,
+    NotInSubgroup,
+    Identity,
+    InvalidScalar,
+    InvalidHex,
+    InvalidBase64,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::InvalidLength { expected, actual } => write!(f, "expected {expected} bytes, got {actual}"),
+            EncodingError::InvalidElement => write!(f, "not the canonical encoding of a group element"),
+            EncodingError::NotInSubgroup => write!(f, "element is not in the order q subgroup"),
+            EncodingError::Identity => write!(f, "element is the identity"),
+            EncodingError::InvalidScalar => write!(f, "scalar is not below q"),
+            EncodingError::InvalidHex => write!(f, "invalid hex string"),
+            EncodingError::InvalidBase64 => write!(f, "invalid base64 string"),
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+// Fixed-width byte encoding: the concatenation of the canonical encodings of
+// the two components, so equal values always produce equal bytes.
+pub trait CanonicalEncoding<G: Group>: Sized {
+    fn to_bytes(&self, group: &G) -> Vec<u8>;
+    fn from_bytes(group: &G, bytes: &[u8]) -> Result<Self, EncodingError>;
+
+    fn to_hex(&self, group: &G) -> String {
+        hex::encode(self.to_bytes(group))
+    }
+
+    fn from_hex(group: &G, text: &str) -> Result<Self, EncodingError> {
+        let bytes = hex::decode(text).map_err(|_| EncodingError::InvalidHex)?;
+        Self::from_bytes(group, &bytes)
+    }
+
+    fn to_base64(&self, group: &G) -> String {
+        BASE64.encode(self.to_bytes(group))
+    }
+
+    fn from_base64(group: &G, text: &str) -> Result<Self, EncodingError> {
+        let bytes = BASE64.decode(text).map_err(|_| EncodingError::InvalidBase64)?;
+        Self::from_bytes(group, &bytes)
+    }
+
+    // Encoding needs the group, so serde goes through these:
+    // `serde_json::to_string(&proof.encoded(&group))` and
+    // `Proof::decoder(&group).deserialize(&mut serde_json::Deserializer::from_str(&json))`
+    fn encoded<'a>(&'a self, group: &'a G) -> Encoded<'a, G, Self> {
+        Encoded { group, value: self }
+    }
+
+    fn decoder(group: &G) -> Decoder<'_, G, Self> {
+        Decoder { group, value: PhantomData }
+    }
+}
+
+// Serializes a value as the hex string of its canonical encoding
+pub struct Encoded<'a, G, T> {
+    group: &'a G,
+    value: &'a T,
+}
+
+impl<G: Group, T: CanonicalEncoding<G>> Serialize for Encoded<'_, G, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.value.to_hex(self.group))
+    }
+}
+
+// Deserializes a value from the hex string of its canonical encoding, with the checks of `from_bytes`
+pub struct Decoder<'a, G, T> {
+    group: &'a G,
+    value: PhantomData<T>,
+}
+
+impl<'de, G: Group, T: CanonicalEncoding<G>> DeserializeSeed<'de> for Decoder<'_, G, T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<G: Group, T: CanonicalEncoding<G>> Visitor<'_> for Decoder<'_, G, T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the hex string of a canonical encoding")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<T, E> {
+        T::from_hex(self.group, text).map_err(E::custom)
+    }
+}
+
+fn split_pair(bytes: &[u8], width: usize) -> Result<(&[u8], &[u8]), EncodingError> {
+    if bytes.len() != 2 * width {
+        return Err(EncodingError::InvalidLength { expected: 2 * width, actual: bytes.len() });
+    }
+    Ok(bytes.split_at(width))
+}
+
+fn decode_elements<G: Group>(group: &G, bytes: &[u8]) -> Result<(G::Element, G::Element), EncodingError> {
+    let (a, b) = split_pair(bytes, group.element_len())?;
+    Ok((group.decode_checked(a)?, group.decode_checked(b)?))
+}
+
+impl<G: Group> CanonicalEncoding<G> for Statement<G> {
+    fn to_bytes(&self, group: &G) -> Vec<u8> {
+        [group.encode_element(&self.y1), group.encode_element(&self.y2)].concat()
+    }
+
+    fn from_bytes(group: &G, bytes: &[u8]) -> Result<Self, EncodingError> {
+        let (y1, y2) = decode_elements(group, bytes)?;
+        Ok(Statement { y1, y2 })
+    }
+}
+
+impl<G: Group> CanonicalEncoding<G> for Commitment<G> {
+    fn to_bytes(&self, group: &G) -> Vec<u8> {
+        [group.encode_element(&self.r1), group.encode_element(&self.r2)].concat()
+    }
+
+    fn from_bytes(group: &G, bytes: &[u8]) -> Result<Self, EncodingError> {
+        let (r1, r2) = decode_elements(group, bytes)?;
+        Ok(Commitment { r1, r2 })
+    }
+}
+
+impl<G: Group> CanonicalEncoding<G> for Proof<G> {
+    fn to_bytes(&self, group: &G) -> Vec<u8> {
+        [group.encode_scalar(&self.c), group.encode_scalar(&self.s)].concat()
+    }
+
+    fn from_bytes(group: &G, bytes: &[u8]) -> Result<Self, EncodingError> {
+        let (c, s) = split_pair(bytes, group.scalar_len())?;
+        Ok(Proof { c: group.decode_scalar_checked(c)?, s: group.decode_scalar_checked(s)? })
+    }
+}
@@ -0,0 +1,132 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoBasepointTable, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, VartimeMultiscalarMul};
+use sha2::Sha512;
+
+use crate::group::Group;
+
+// Public seed hashed to the curve to obtain the second generator `beta`. Nobody
+// knows log_alpha(beta) because it is the output of a hash.
+pub const BETA_SEED: &[u8] = b"zkp-grpc/ristretto255/beta";
+
+// Prime-order group built on Curve25519, elements and scalars both encode to 32 bytes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RistrettoGroup;
+
+impl RistrettoGroup {
+    pub fn new() -> Self {
+        RistrettoGroup
+    }
+
+    // (alpha, beta)
+    pub fn generators() -> (RistrettoPoint, RistrettoPoint) {
+        let alpha = RISTRETTO_BASEPOINT_POINT;
+        let beta = RistrettoGroup.hash_to_element(BETA_SEED);
+        (alpha, beta)
+    }
+}
+
+impl Group for RistrettoGroup {
+    type Element = RistrettoPoint;
+    type Scalar = Scalar;
+    type Table = RistrettoBasepointTable;
+
+    fn identity(&self) -> RistrettoPoint {
+        RistrettoPoint::identity()
+    }
+
+    fn op(&self, a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
+        a + b
+    }
+
+    fn exp(&self, base: &RistrettoPoint, exponent: &Scalar) -> RistrettoPoint {
+        base * exponent
+    }
+
+    fn random_scalar(&self) -> Scalar {
+        Scalar::random(&mut rand::thread_rng())
+    }
+
+    fn scalar_mul(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a * b
+    }
+
+    fn scalar_sub(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a - b
+    }
+
+    fn scalar_add(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a + b
+    }
+
+    fn scalar_neg(&self, a: &Scalar) -> Scalar {
+        -a
+    }
+
+    fn precompute(&self, base: &RistrettoPoint) -> RistrettoBasepointTable {
+        RistrettoBasepointTable::create(base)
+    }
+
+    fn exp_fixed(&self, table: &RistrettoBasepointTable, exponent: &Scalar) -> RistrettoPoint {
+        table * exponent
+    }
+
+    // Straus double scalar multiplication, only ever used on public values
+    // so variable time is fine
+    fn exp2(&self, a: &RistrettoPoint, _table: &RistrettoBasepointTable, x: &Scalar, b: &RistrettoPoint, y: &Scalar) -> RistrettoPoint {
+        RistrettoPoint::vartime_multiscalar_mul([x, y], [a, b])
+    }
+
+    // only ever used on public values, so variable time is fine
+    fn multi_exp(&self, terms: &[(RistrettoPoint, Scalar)]) -> RistrettoPoint {
+        RistrettoPoint::vartime_multiscalar_mul(terms.iter().map(|(_, scalar)| scalar), terms.iter().map(|(point, _)| point))
+    }
+
+    fn hash_to_scalar(&self, bytes: &[u8]) -> Scalar {
+        Scalar::hash_from_bytes::<Sha512>(bytes)
+    }
+
+    fn scalar_from_wide_bytes(&self, bytes: &[u8]) -> Scalar {
+        let wide: &[u8; 64] = bytes.try_into().expect("wrong number of bytes to reduce");
+        Scalar::from_bytes_mod_order_wide(wide)
+    }
+
+    fn hash_to_element(&self, seed: &[u8]) -> RistrettoPoint {
+        RistrettoPoint::hash_from_bytes::<Sha512>(seed)
+    }
+
+    fn transcript_label(&self) -> Vec<u8> {
+        b"ristretto255".to_vec()
+    }
+
+    fn element_len(&self) -> usize {
+        32
+    }
+
+    fn scalar_len(&self) -> usize {
+        32
+    }
+
+    fn encode_element(&self, element: &RistrettoPoint) -> Vec<u8> {
+        element.compress().to_bytes().to_vec()
+    }
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<RistrettoPoint> {
+        CompressedRistretto::from_slice(bytes).ok()?.decompress()
+    }
+
+    fn encode_scalar(&self, scalar: &Scalar) -> Vec<u8> {
+        scalar.to_bytes().to_vec()
+    }
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<Scalar> {
+        let bytes: [u8; 32] = bytes.try_into().ok()?;
+        Scalar::from_canonical_bytes(bytes).into()
+    }
+
+    // Ristretto255 has prime order, every decodable point is in the group
+    fn in_subgroup(&self, _element: &RistrettoPoint) -> bool {
+        true
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+use tonic::Status;
+
+use crate::zkp_auth::auth_client::AuthClient;
+use crate::zkp_auth::{AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationChallengeRequest, LoginRequest, RegisterRequest, SaltRequest};
+use crate::zkp_auth::{LogoutRequest, RefreshSessionRequest, RefreshSessionResponse, RotateCredentialsRequest, ValidateSessionRequest};
+use crate::{derive_secret, generate_salt, login_context, rotate_context, EncodingError, Group, KdfError, KdfParams, NonceStrategy, ZKP};
+
+// A session issued by a successful login
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Session {
+    pub session_id: String,
+    // unix time in seconds
+    pub expires_at: u64,
+    // signed JWT, when the server has a signing key
+    pub token: Option<String>,
+}
+
+// What the server knows about a valid session
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub user: String,
+    // unix time in seconds
+    pub expires_at: u64,
+}
+
+// TLS settings of a connection, certificates and keys in PEM
+#[derive(Clone, Debug, Default)]
+pub struct TlsOptions {
+    // CA that signed the server certificate, the system roots when absent
+    pub ca_cert: Option<Vec<u8>>,
+    // certificate and key of the client, for servers that require mutual TLS
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+    // name the server certificate must be valid for, the host of the URL when absent
+    pub domain: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Transport(tonic::transport::Error),
+    // the server refused the call, boxed as it is much larger than the other variants
+    Status(Box<Status>),
+    Kdf(KdfError),
+    // the server sent a challenge that is not a scalar of the group
+    InvalidChallenge(EncodingError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // the transport error alone only says "transport error"
+            ClientError::Transport(err) => match std::error::Error::source(err) {
+                Some(source) => write!(f, "{err}: {source}"),
+                None => write!(f, "{err}"),
+            },
+            ClientError::Status(status) => write!(f, "{} ({:?})", status.message(), status.code()),
+            ClientError::Kdf(err) => write!(f, "could not derive secret: {err}"),
+            ClientError::InvalidChallenge(err) => write!(f, "invalid challenge: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl ClientError {
+    // How long the server asked to wait before trying again, when it refused the call with
+    // RESOURCE_EXHAUSTED because of a rate limit or a locked account
+    pub fn retry_after(&self) -> Option<Duration> {
+        let ClientError::Status(status) = self else { return None };
+        let secs = status.metadata().get("retry-after")?.to_str().ok()?.parse().ok()?;
+        Some(Duration::from_secs(secs))
+    }
+}
+
+impl From<tonic::transport::Error> for ClientError {
+    fn from(err: tonic::transport::Error) -> Self {
+        ClientError::Transport(err)
+    }
+}
+
+impl From<Status> for ClientError {
+    fn from(status: Status) -> Self {
+        ClientError::Status(Box::new(status))
+    }
+}
+
+impl From<KdfError> for ClientError {
+    fn from(err: KdfError) -> Self {
+        ClientError::Kdf(err)
+    }
+}
+
+// Client of the auth service that takes passwords and does the protocol math and the wire
+// encoding itself. The group must be the one the server runs with.
+pub struct ZkpAuthClient<G: Group> {
+    client: AuthClient<Channel>,
+    zkp: ZKP<G>,
+    kdf_params: KdfParams,
+}
+
+impl<G: Group> ZkpAuthClient<G> {
+    // Connects to a server URL such as `http://127.0.0.1:50051`
+    pub async fn connect(url: impl Into<String>, zkp: ZKP<G>) -> Result<Self, ClientError> {
+        let channel = Endpoint::from_shared(url.into())?.connect().await?;
+        Ok(Self::new(channel, zkp))
+    }
+
+    // Connects to a server URL such as `https://auth.example.com:50051` over TLS
+    pub async fn connect_tls(url: impl Into<String>, tls: &TlsOptions, zkp: ZKP<G>) -> Result<Self, ClientError> {
+        let mut config = match &tls.ca_cert {
+            Some(ca_cert) => ClientTlsConfig::new().ca_certificate(Certificate::from_pem(ca_cert)),
+            None => ClientTlsConfig::new().with_native_roots(),
+        };
+        if let Some((cert, key)) = &tls.identity {
+            config = config.identity(Identity::from_pem(cert, key));
+        }
+        if let Some(domain) = &tls.domain {
+            config = config.domain_name(domain);
+        }
+        let channel = Endpoint::from_shared(url.into())?.tls_config(config)?.connect().await?;
+        Ok(Self::new(channel, zkp))
+    }
+
+    pub fn new(channel: Channel, zkp: ZKP<G>) -> Self {
+        ZkpAuthClient { client: AuthClient::new(channel), zkp, kdf_params: KdfParams::default() }
+    }
+
+    // Argon2 cost of the secret derivation, it must be the same for registration and login
+    pub fn with_kdf_params(mut self, kdf_params: KdfParams) -> Self {
+        self.kdf_params = kdf_params;
+        self
+    }
+
+    pub fn zkp(&self) -> &ZKP<G> {
+        &self.zkp
+    }
+
+    // the generated client, for calls this type does not wrap
+    pub fn inner_mut(&mut self) -> &mut AuthClient<Channel> {
+        &mut self.client
+    }
+
+    // Registers a user, the secret is derived from the password with a fresh salt
+    pub async fn register(&mut self, user: &str, password: impl AsRef<[u8]>) -> Result<(), ClientError> {
+        let salt = generate_salt();
+        let statement = self.zkp.statement(&self.derive(password.as_ref(), &salt)?);
+        let request = RegisterRequest {
+            user_name: user.to_string(),
+            y1: self.zkp.group.encode_element(&statement.y1),
+            y2: self.zkp.group.encode_element(&statement.y2),
+            salt,
+        };
+        self.client.register(request).await?;
+        Ok(())
+    }
+
+    // Logs in with the interactive challenge/response flow
+    pub async fn login(&mut self, user: &str, password: impl AsRef<[u8]>) -> Result<Session, ClientError> {
+        let group = &self.zkp.group;
+        // x is only known once the challenge response carries the salt, so k cannot be derived from it
+        let k = group.random_scalar();
+        let commitment = self.zkp.commitment(&k);
+        let request = AuthenticationChallengeRequest {
+            user: user.to_string(),
+            r1: group.encode_element(&commitment.r1),
+            r2: group.encode_element(&commitment.r2),
+        };
+        let response = self.client.create_authentication_challenge(request).await?.into_inner();
+        let c = group.decode_scalar_checked(&response.c).map_err(ClientError::InvalidChallenge)?;
+
+        let s = self.zkp.solve(&k, &c, &self.derive(password.as_ref(), &response.salt)?);
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: self.zkp.group.encode_scalar(&s) };
+        Ok(self.client.verify_authentication(request).await?.into_inner().into())
+    }
+
+    // Logs in with a single Fiat-Shamir proof, its nonce is hedged
+    pub async fn login_noninteractive(&mut self, user: &str, password: impl AsRef<[u8]>) -> Result<Session, ClientError> {
+        let x = self.secret(user, password.as_ref()).await?;
+        let timestamp = unix_now();
+        let proof = self.zkp.prove_noninteractive_with(user, &x, &login_context(timestamp), NonceStrategy::Hedged);
+        let request = LoginRequest {
+            user: user.to_string(),
+            timestamp,
+            c: self.zkp.group.encode_scalar(&proof.c),
+            s: self.zkp.group.encode_scalar(&proof.s),
+        };
+        Ok(self.client.login(request).await?.into_inner().into())
+    }
+
+    // Replaces the password of a user, authorized by a proof for the current one
+    pub async fn rotate(&mut self, user: &str, password: impl AsRef<[u8]>, new_password: impl AsRef<[u8]>) -> Result<(), ClientError> {
+        let x = self.secret(user, password.as_ref()).await?;
+        let new_salt = generate_salt();
+        let new = self.zkp.statement(&self.derive(new_password.as_ref(), &new_salt)?);
+        let (new_y1, new_y2) = (self.zkp.group.encode_element(&new.y1), self.zkp.group.encode_element(&new.y2));
+
+        // the proof for the current keys binds the new ones, it cannot install any others
+        let timestamp = unix_now();
+        let context = rotate_context(timestamp, &new_y1, &new_y2, &new_salt);
+        let proof = self.zkp.prove_noninteractive_with(user, &x, &context, NonceStrategy::Hedged);
+        let request = RotateCredentialsRequest {
+            user: user.to_string(),
+            timestamp,
+            c: self.zkp.group.encode_scalar(&proof.c),
+            s: self.zkp.group.encode_scalar(&proof.s),
+            new_y1,
+            new_y2,
+            new_salt,
+        };
+        self.client.rotate_credentials(request).await?;
+        Ok(())
+    }
+
+    // The user a session belongs to, fails if it is unknown or expired
+    pub async fn validate_session(&mut self, session_id: &str) -> Result<SessionInfo, ClientError> {
+        let request = ValidateSessionRequest { session_id: session_id.to_string() };
+        let response = self.client.validate_session(request).await?.into_inner();
+        Ok(SessionInfo { user: response.user, expires_at: response.expires_at })
+    }
+
+    // Swaps a session for a new one with a later expiry, the old id stops working
+    pub async fn refresh_session(&mut self, session_id: &str) -> Result<Session, ClientError> {
+        let request = RefreshSessionRequest { session_id: session_id.to_string() };
+        Ok(self.client.refresh_session(request).await?.into_inner().into())
+    }
+
+    pub async fn logout(&mut self, session_id: &str) -> Result<(), ClientError> {
+        self.client.logout(LogoutRequest { session_id: session_id.to_string() }).await?;
+        Ok(())
+    }
+
+    fn derive(&self, password: &[u8], salt: &[u8]) -> Result<G::Scalar, ClientError> {
+        Ok(derive_secret(&self.zkp.group, password, salt, &self.kdf_params)?)
+    }
+
+    // the registered secret, derived with the salt the server hands out
+    async fn secret(&mut self, user: &str, password: &[u8]) -> Result<G::Scalar, ClientError> {
+        let salt = self.client.get_salt(SaltRequest { user: user.to_string() }).await?.into_inner().salt;
+        self.derive(password, &salt)
+    }
+}
+
+impl From<AuthenticationAnswerResponse> for Session {
+    fn from(response: AuthenticationAnswerResponse) -> Self {
+        Session { session_id: response.session_id, expires_at: response.expires_at, token: non_empty(response.token) }
+    }
+}
+
+impl From<RefreshSessionResponse> for Session {
+    fn from(response: RefreshSessionResponse) -> Self {
+        Session { session_id: response.session_id, expires_at: response.expires_at, token: non_empty(response.token) }
+    }
+}
+
+// tokens are empty when the server does not sign them
+fn non_empty(token: String) -> Option<String> {
+    Some(token).filter(|token| !token.is_empty())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("Clock is before epoch").as_secs()
+}
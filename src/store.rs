@@ -0,0 +1,145 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+mod memory;
+mod sqlite;
+
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+// Group elements and scalars are kept in their wire encoding, which only the group that
+// made them can decode. A database remembers its group and `open` refuses any other.
+
+// Registration of a user
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserInfo {
+    pub user_name: String,
+    pub y1: Vec<u8>,
+    pub y2: Vec<u8>,
+    pub salt: Vec<u8>,
+}
+
+// One login attempt: the commitment of the prover and the challenge it was given.
+// Every attempt has its own record, so a user can log in from several devices at once.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PendingChallenge {
+    pub auth_id: String,
+    pub user: String,
+    pub r1: Vec<u8>,
+    pub r2: Vec<u8>,
+    pub c: Vec<u8>,
+    // unix time in seconds
+    pub created_at: u64,
+}
+
+// A session issued after a successful login
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Session {
+    pub session_id: String,
+    pub user: String,
+    // unix times in seconds
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Sqlite(rusqlite::Error),
+    // the database was written by a newer version with more migrations
+    UnsupportedSchema(u32),
+    // the database holds the keys of another group than the server runs with
+    GroupMismatch { stored: String, configured: String },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(err) => write!(f, "sqlite: {err}"),
+            StoreError::UnsupportedSchema(version) => write!(f, "unsupported schema version {version}"),
+            StoreError::GroupMismatch { stored, configured } => {
+                write!(f, "the database holds keys of group {stored}, not {configured}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> Self {
+        StoreError::Sqlite(err)
+    }
+}
+
+// Registered users, keyed by user name
+pub trait UserStore: Send + Sync {
+    fn get_user(&self, user_name: &str) -> Result<Option<UserInfo>, StoreError>;
+
+    // inserts a new user, returns false without touching anything if the name is taken
+    fn insert_user(&self, user: &UserInfo) -> Result<bool, StoreError>;
+
+    // replaces the record of `new.user_name` only if it still equals `current`,
+    // returns whether it did. Two concurrent rotations cannot both succeed.
+    fn replace_user(&self, current: &UserInfo, new: &UserInfo) -> Result<bool, StoreError>;
+}
+
+// Challenges handed out by the server, keyed by auth id
+pub trait ChallengeStore: Send + Sync {
+    fn put_challenge(&self, challenge: &PendingChallenge) -> Result<(), StoreError>;
+
+    // Removes and returns the challenge in one atomic step, so that of two
+    // concurrent answers to the same challenge only one ever sees it
+    fn take_challenge(&self, auth_id: &str) -> Result<Option<PendingChallenge>, StoreError>;
+
+    // drops every challenge created before `created_before`, returns how many
+    fn remove_expired_challenges(&self, created_before: u64) -> Result<usize, StoreError>;
+}
+
+// Issued sessions, keyed by session id. Expiry is checked by the caller.
+pub trait SessionStore: Send + Sync {
+    fn put_session(&self, session: &Session) -> Result<(), StoreError>;
+
+    fn get_session(&self, session_id: &str) -> Result<Option<Session>, StoreError>;
+
+    // returns whether the session existed
+    fn remove_session(&self, session_id: &str) -> Result<bool, StoreError>;
+
+    // drops every session that expired before `expired_before`, returns how many
+    fn remove_expired_sessions(&self, expired_before: u64) -> Result<usize, StoreError>;
+
+    // drops every session of a user, returns how many
+    fn remove_user_sessions(&self, user: &str) -> Result<usize, StoreError>;
+}
+
+// Non-interactive proofs the server accepted, keyed by user and challenge. They are kept
+// until their timestamp is too old to be accepted anyway, so that none is accepted twice.
+pub trait ProofStore: Send + Sync {
+    // records a proof, returns false if it was already recorded
+    fn insert_proof(&self, user: &str, c: &[u8], expires_at: u64) -> Result<bool, StoreError>;
+
+    // whether a proof was recorded
+    fn has_proof(&self, user: &str, c: &[u8]) -> Result<bool, StoreError>;
+
+    // drops every proof that expired before `expired_before`, returns how many
+    fn remove_expired_proofs(&self, expired_before: u64) -> Result<usize, StoreError>;
+}
+
+// Everything the server persists
+pub trait Store: UserStore + ChallengeStore + SessionStore + ProofStore {
+    // Removes the registration, the pending challenges and the sessions of a user in
+    // one step, nobody sees a session outliving its account. Returns whether the user existed.
+    fn delete_account(&self, user: &str) -> Result<bool, StoreError>;
+}
+
+// A SQLite store at `path` for the keys of `group` when given, an in-memory one otherwise
+pub fn open(path: Option<&Path>, group: &str) -> Result<Arc<dyn Store>, StoreError> {
+    Ok(match path {
+        Some(path) => {
+            let store = SqliteStore::open(path)?;
+            store.bind_group(group)?;
+            Arc::new(store)
+        }
+        None => Arc::new(MemoryStore::default()),
+    })
+}
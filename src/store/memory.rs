@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::{ChallengeStore, PendingChallenge, ProofStore, Session, SessionStore, Store, StoreError, UserInfo, UserStore};
+
+// Process-local store, everything is lost on restart.
+// Methods that need several maps lock them in declaration order.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    users: Mutex<HashMap<String, UserInfo>>,
+    challenges: Mutex<HashMap<String, PendingChallenge>>,
+    sessions: Mutex<HashMap<String, Session>>,
+    // expiry of the accepted proofs by user and challenge
+    proofs: Mutex<HashMap<(String, Vec<u8>), u64>>,
+}
+
+impl UserStore for MemoryStore {
+    fn get_user(&self, user_name: &str) -> Result<Option<UserInfo>, StoreError> {
+        Ok(self.users.lock().unwrap().get(user_name).cloned())
+    }
+
+    fn insert_user(&self, user: &UserInfo) -> Result<bool, StoreError> {
+        let mut users = self.users.lock().unwrap();
+        if users.contains_key(&user.user_name) {
+            return Ok(false);
+        }
+        users.insert(user.user_name.clone(), user.clone());
+        Ok(true)
+    }
+
+    fn replace_user(&self, current: &UserInfo, new: &UserInfo) -> Result<bool, StoreError> {
+        let mut users = self.users.lock().unwrap();
+        match users.get_mut(&new.user_name) {
+            Some(user) if user == current => {
+                *user = new.clone();
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+impl ChallengeStore for MemoryStore {
+    fn put_challenge(&self, challenge: &PendingChallenge) -> Result<(), StoreError> {
+        self.challenges.lock().unwrap().insert(challenge.auth_id.clone(), challenge.clone());
+        Ok(())
+    }
+
+    fn take_challenge(&self, auth_id: &str) -> Result<Option<PendingChallenge>, StoreError> {
+        Ok(self.challenges.lock().unwrap().remove(auth_id))
+    }
+
+    fn remove_expired_challenges(&self, created_before: u64) -> Result<usize, StoreError> {
+        let mut challenges = self.challenges.lock().unwrap();
+        let before = challenges.len();
+        challenges.retain(|_, challenge| challenge.created_at >= created_before);
+        Ok(before - challenges.len())
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn put_session(&self, session: &Session) -> Result<(), StoreError> {
+        self.sessions.lock().unwrap().insert(session.session_id.clone(), session.clone());
+        Ok(())
+    }
+
+    fn get_session(&self, session_id: &str) -> Result<Option<Session>, StoreError> {
+        Ok(self.sessions.lock().unwrap().get(session_id).cloned())
+    }
+
+    fn remove_session(&self, session_id: &str) -> Result<bool, StoreError> {
+        Ok(self.sessions.lock().unwrap().remove(session_id).is_some())
+    }
+
+    fn remove_expired_sessions(&self, expired_before: u64) -> Result<usize, StoreError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, session| session.expires_at >= expired_before);
+        Ok(before - sessions.len())
+    }
+
+    fn remove_user_sessions(&self, user: &str) -> Result<usize, StoreError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let before = sessions.len();
+        sessions.retain(|_, session| session.user != user);
+        Ok(before - sessions.len())
+    }
+}
+
+impl ProofStore for MemoryStore {
+    fn insert_proof(&self, user: &str, c: &[u8], expires_at: u64) -> Result<bool, StoreError> {
+        let mut proofs = self.proofs.lock().unwrap();
+        if proofs.contains_key(&(user.to_string(), c.to_vec())) {
+            return Ok(false);
+        }
+        proofs.insert((user.to_string(), c.to_vec()), expires_at);
+        Ok(true)
+    }
+
+    fn has_proof(&self, user: &str, c: &[u8]) -> Result<bool, StoreError> {
+        Ok(self.proofs.lock().unwrap().contains_key(&(user.to_string(), c.to_vec())))
+    }
+
+    fn remove_expired_proofs(&self, expired_before: u64) -> Result<usize, StoreError> {
+        let mut proofs = self.proofs.lock().unwrap();
+        let before = proofs.len();
+        proofs.retain(|_, expires_at| *expires_at >= expired_before);
+        Ok(before - proofs.len())
+    }
+}
+
+impl Store for MemoryStore {
+    fn delete_account(&self, user: &str) -> Result<bool, StoreError> {
+        let mut users = self.users.lock().unwrap();
+        let mut challenges = self.challenges.lock().unwrap();
+        let mut sessions = self.sessions.lock().unwrap();
+        challenges.retain(|_, challenge| challenge.user != user);
+        sessions.retain(|_, session| session.user != user);
+        Ok(users.remove(user).is_some())
+    }
+}
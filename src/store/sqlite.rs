@@ -0,0 +1,254 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{ChallengeStore, PendingChallenge, ProofStore, Session, SessionStore, Store, StoreError, UserInfo, UserStore};
+
+// Schema migrations, MIGRATIONS[i] takes the database from version i to i + 1.
+// The version is kept in SQLite's `user_version`. Only ever append to this list.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE users (
+        user_name TEXT PRIMARY KEY,
+        y1        BLOB NOT NULL,
+        y2        BLOB NOT NULL,
+        salt      BLOB NOT NULL
+    );
+    CREATE TABLE challenges (
+        auth_id    TEXT PRIMARY KEY,
+        user_name  TEXT NOT NULL,
+        r1         BLOB NOT NULL,
+        r2         BLOB NOT NULL,
+        c          BLOB NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+", "
+    -- the sweeper deletes expired challenges by age
+    CREATE INDEX challenges_created_at ON challenges (created_at);
+", "
+    CREATE TABLE sessions (
+        session_id TEXT PRIMARY KEY,
+        user_name  TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        expires_at INTEGER NOT NULL
+    );
+    CREATE INDEX sessions_expires_at ON sessions (expires_at);
+", "
+    -- revoking sessions and deleting accounts look rows up by user
+    CREATE INDEX challenges_user_name ON challenges (user_name);
+    CREATE INDEX sessions_user_name ON sessions (user_name);
+", "
+    CREATE TABLE proofs (
+        user_name  TEXT NOT NULL,
+        c          BLOB NOT NULL,
+        expires_at INTEGER NOT NULL,
+        PRIMARY KEY (user_name, c)
+    );
+    CREATE INDEX proofs_expires_at ON proofs (expires_at);
+", "
+    -- settings the records depend on, such as the group
+    CREATE TABLE meta (
+        key   TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+"];
+
+// File-backed store, registrations survive restarts
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::migrate(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::migrate(Connection::open_in_memory()?)
+    }
+
+    fn migrate(mut conn: Connection) -> Result<Self, StoreError> {
+        let tx = conn.transaction()?;
+        let version: u32 = tx.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version as usize > MIGRATIONS.len() {
+            return Err(StoreError::UnsupportedSchema(version));
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            tx.execute_batch(migration)?;
+        }
+        tx.pragma_update(None, "user_version", MIGRATIONS.len() as u32)?;
+        tx.commit()?;
+        Ok(SqliteStore { conn: Mutex::new(conn) })
+    }
+
+    // Records `group` as the group of the keys in the database the first time, and fails if
+    // it holds the keys of another one. Databases from before it was recorded take the first
+    // group they are opened with.
+    pub fn bind_group(&self, group: &str) -> Result<(), StoreError> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("INSERT OR IGNORE INTO meta (key, value) VALUES ('group', ?1)", [group])?;
+        let stored: String = conn.query_row("SELECT value FROM meta WHERE key = 'group'", [], |row| row.get(0))?;
+        if stored != group {
+            return Err(StoreError::GroupMismatch { stored, configured: group.to_string() });
+        }
+        Ok(())
+    }
+
+    // schema version of the open database
+    pub fn version(&self) -> Result<u32, StoreError> {
+        Ok(self.conn.lock().unwrap().query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
+}
+
+impl UserStore for SqliteStore {
+    fn get_user(&self, user_name: &str) -> Result<Option<UserInfo>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let user = conn
+            .query_row(
+                "SELECT user_name, y1, y2, salt FROM users WHERE user_name = ?1",
+                [user_name],
+                |row| {
+                    Ok(UserInfo {
+                        user_name: row.get(0)?,
+                        y1: row.get(1)?,
+                        y2: row.get(2)?,
+                        salt: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(user)
+    }
+
+    fn insert_user(&self, user: &UserInfo) -> Result<bool, StoreError> {
+        let inserted = self.conn.lock().unwrap().execute(
+            "INSERT OR IGNORE INTO users (user_name, y1, y2, salt) VALUES (?1, ?2, ?3, ?4)",
+            params![user.user_name, user.y1, user.y2, user.salt],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    fn replace_user(&self, current: &UserInfo, new: &UserInfo) -> Result<bool, StoreError> {
+        let updated = self.conn.lock().unwrap().execute(
+            "UPDATE users SET y1 = ?2, y2 = ?3, salt = ?4
+             WHERE user_name = ?1 AND y1 = ?5 AND y2 = ?6 AND salt = ?7",
+            params![new.user_name, new.y1, new.y2, new.salt, current.y1, current.y2, current.salt],
+        )?;
+        Ok(updated > 0)
+    }
+}
+
+impl ChallengeStore for SqliteStore {
+    fn put_challenge(&self, challenge: &PendingChallenge) -> Result<(), StoreError> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO challenges (auth_id, user_name, r1, r2, c, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![challenge.auth_id, challenge.user, challenge.r1, challenge.r2, challenge.c, challenge.created_at],
+        )?;
+        Ok(())
+    }
+
+    fn take_challenge(&self, auth_id: &str) -> Result<Option<PendingChallenge>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let challenge = conn
+            .query_row(
+                "DELETE FROM challenges WHERE auth_id = ?1 RETURNING auth_id, user_name, r1, r2, c, created_at",
+                [auth_id],
+                |row| {
+                    Ok(PendingChallenge {
+                        auth_id: row.get(0)?,
+                        user: row.get(1)?,
+                        r1: row.get(2)?,
+                        r2: row.get(3)?,
+                        c: row.get(4)?,
+                        created_at: row.get(5)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(challenge)
+    }
+
+    fn remove_expired_challenges(&self, created_before: u64) -> Result<usize, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM challenges WHERE created_at < ?1", [created_before])?)
+    }
+}
+
+impl SessionStore for SqliteStore {
+    fn put_session(&self, session: &Session) -> Result<(), StoreError> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO sessions (session_id, user_name, created_at, expires_at) VALUES (?1, ?2, ?3, ?4)",
+            params![session.session_id, session.user, session.created_at, session.expires_at],
+        )?;
+        Ok(())
+    }
+
+    fn get_session(&self, session_id: &str) -> Result<Option<Session>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let session = conn
+            .query_row(
+                "SELECT session_id, user_name, created_at, expires_at FROM sessions WHERE session_id = ?1",
+                [session_id],
+                |row| {
+                    Ok(Session {
+                        session_id: row.get(0)?,
+                        user: row.get(1)?,
+                        created_at: row.get(2)?,
+                        expires_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(session)
+    }
+
+    fn remove_session(&self, session_id: &str) -> Result<bool, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM sessions WHERE session_id = ?1", [session_id])? > 0)
+    }
+
+    fn remove_expired_sessions(&self, expired_before: u64) -> Result<usize, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM sessions WHERE expires_at < ?1", [expired_before])?)
+    }
+
+    fn remove_user_sessions(&self, user: &str) -> Result<usize, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM sessions WHERE user_name = ?1", [user])?)
+    }
+}
+
+impl ProofStore for SqliteStore {
+    fn insert_proof(&self, user: &str, c: &[u8], expires_at: u64) -> Result<bool, StoreError> {
+        let inserted = self.conn.lock().unwrap().execute(
+            "INSERT OR IGNORE INTO proofs (user_name, c, expires_at) VALUES (?1, ?2, ?3)",
+            params![user, c, expires_at],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    fn has_proof(&self, user: &str, c: &[u8]) -> Result<bool, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let found = conn
+            .query_row("SELECT 1 FROM proofs WHERE user_name = ?1 AND c = ?2", params![user, c], |_| Ok(()))
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    fn remove_expired_proofs(&self, expired_before: u64) -> Result<usize, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM proofs WHERE expires_at < ?1", [expired_before])?)
+    }
+}
+
+impl Store for SqliteStore {
+    fn delete_account(&self, user: &str) -> Result<bool, StoreError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM challenges WHERE user_name = ?1", [user])?;
+        tx.execute("DELETE FROM sessions WHERE user_name = ?1", [user])?;
+        let deleted = tx.execute("DELETE FROM users WHERE user_name = ?1", [user])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+}
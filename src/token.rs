@@ -0,0 +1,122 @@
+use std::fmt;
+use std::path::Path;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// What a session token asserts: `sub` logged in at `iat` and the session lasts until `exp`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    // unix times in seconds
+    pub iat: u64,
+    pub exp: u64,
+    // the challenge that was answered, absent for non-interactive logins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_id: Option<String>,
+}
+
+// Public half of a signing key in JWK form (RFC 8037), what verifiers need
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKey {
+    pub kid: String,
+    pub kty: String,
+    pub crv: String,
+    pub alg: String,
+    // base64url of the 32-byte Ed25519 public key
+    pub x: String,
+}
+
+#[derive(Debug)]
+pub enum TokenError {
+    Io(std::io::Error),
+    InvalidPem,
+    InvalidKey,
+    Jwt(jsonwebtoken::errors::Error),
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenError::Io(err) => write!(f, "could not read key: {err}"),
+            TokenError::InvalidPem => write!(f, "key is not a PEM encoded PKCS#8 private key"),
+            TokenError::InvalidKey => write!(f, "key is not an Ed25519 key"),
+            TokenError::Jwt(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+impl From<jsonwebtoken::errors::Error> for TokenError {
+    fn from(err: jsonwebtoken::errors::Error) -> Self {
+        TokenError::Jwt(err)
+    }
+}
+
+// Signs session tokens as JWTs with EdDSA over Ed25519
+pub struct TokenSigner {
+    key: EncodingKey,
+    public_key: PublicKey,
+}
+
+impl TokenSigner {
+    // PKCS#8 DER, as written by `openssl genpkey -algorithm ed25519 -outform der`
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, TokenError> {
+        let pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der).map_err(|_| TokenError::InvalidKey)?;
+        Ok(TokenSigner {
+            key: EncodingKey::from_ed_der(der),
+            public_key: PublicKey::ed25519(pair.public_key().as_ref()),
+        })
+    }
+
+    // PKCS#8 PEM, as written by `openssl genpkey -algorithm ed25519`
+    pub fn from_pem(pem: &[u8]) -> Result<Self, TokenError> {
+        let pem = pem::parse(pem).map_err(|_| TokenError::InvalidPem)?;
+        if pem.tag() != "PRIVATE KEY" {
+            return Err(TokenError::InvalidPem);
+        }
+        Self::from_pkcs8_der(pem.contents())
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, TokenError> {
+        Self::from_pem(&std::fs::read(path).map_err(TokenError::Io)?)
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    pub fn sign(&self, claims: &Claims) -> Result<String, TokenError> {
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(self.public_key.kid.clone());
+        Ok(jsonwebtoken::encode(&header, claims, &self.key)?)
+    }
+}
+
+impl PublicKey {
+    fn ed25519(public_key: &[u8]) -> Self {
+        let x = BASE64_URL.encode(public_key);
+        // RFC 7638 thumbprint: the required members in lexicographic order, no whitespace
+        let thumbprint = format!(r#"{{"crv":"Ed25519","kty":"OKP","x":"{x}"}}"#);
+        PublicKey {
+            kid: BASE64_URL.encode(Sha256::digest(thumbprint)),
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            alg: "EdDSA".to_string(),
+            x,
+        }
+    }
+
+    // Checks the signature and the expiry of a token signed with this key
+    pub fn verify(&self, token: &str) -> Result<Claims, TokenError> {
+        let key = DecodingKey::from_ed_components(&self.x)?;
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.leeway = 0;
+        Ok(jsonwebtoken::decode::<Claims>(token, &key, &validation)?.claims)
+    }
+}
@@ -0,0 +1,45 @@
+// End-to-end tests of the `client` binary against the `server` binary
+mod common;
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use serde_json::Value;
+
+use common::{start_server, Server};
+
+// Runs the client with `stdin` as its standard input and parses the JSON it prints
+fn client(server: &Server, args: &[&str], stdin: &str) -> (Output, Value) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_client"))
+        .args(["--server", &format!("http://127.0.0.1:{}", server.port), "--group", "ristretto255", "--json"])
+        .args(args)
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Could not start client");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let json = serde_json::from_slice(&output.stdout).unwrap_or_else(|err| panic!("{err}: {output:?}"));
+    (output, json)
+}
+
+#[test]
+fn test_password_fd() {
+    let server = start_server(&[]);
+
+    let (output, json) = client(&server, &["register", "-u", "alice", "--password-fd", "0"], "secret\n");
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(json["user"], "alice");
+    let (output, json) = client(&server, &["login", "-u", "alice", "--password-fd", "0"], "secret\n");
+    assert!(output.status.success(), "{output:?}");
+    assert!(json["session_id"].is_string());
+
+    // a descriptor that is not open, and the ones the output goes to, are refused with an error
+    for fd in ["9", "1", "2"] {
+        let (output, json) = client(&server, &["register", "-u", "bob", "--password-fd", fd], "");
+        assert_eq!(output.status.code(), Some(1), "{output:?}");
+        assert!(json["error"].as_str().unwrap().contains(&format!("fd {fd}")), "{json}");
+    }
+}
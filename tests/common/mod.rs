@@ -0,0 +1,54 @@
+// Starts the `server` binary for the end-to-end tests
+// Every test crate includes this module and uses only part of it
+#![allow(dead_code)]
+
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use zkp::{KdfParams, RistrettoGroup, ZKP};
+
+// cheap Argon2, the derivation itself is tested in the library
+pub const KDF_PARAMS: KdfParams = KdfParams { memory: 64, iterations: 1, parallelism: 1 };
+
+// the server is killed when the test ends, whether it passes or not
+pub struct Server {
+    child: Child,
+    pub port: u16,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Starts a server with a fresh in-memory store on a free port and waits until it accepts connections
+pub fn start_server(args: &[&str]) -> Server {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let child = Command::new(env!("CARGO_BIN_EXE_server"))
+        .args(["--listen", &format!("127.0.0.1:{port}"), "--group", "ristretto255", "--log-level", "warn"])
+        .args(args)
+        // ZKP_* settings of whoever runs the tests must not leak in
+        .env_clear()
+        .spawn()
+        .expect("Could not start server");
+    let mut server = Server { child, port };
+
+    for _ in 0..100 {
+        if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+            return server;
+        }
+        if let Some(status) = server.child.try_wait().unwrap() {
+            panic!("Server exited with {status}");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    panic!("Server did not come up on port {port}");
+}
+
+pub fn zkp() -> ZKP<RistrettoGroup> {
+    let (alpha, beta) = RistrettoGroup::generators();
+    ZKP::new(RistrettoGroup::new(), alpha, beta)
+}
@@ -0,0 +1,117 @@
+// dudect-style timing tests for the secret-dependent operations.
+//
+// Each test times an operation on two classes of secrets, a fixed one and fresh
+// random ones, interleaved in random order, and runs Welch's t-test on the two
+// timing distributions. |t| above 4.5 means the timing depends on the secret.
+// The fixed secret has the full width of q but only two bits set, which is what
+// a square-and-multiply or digit-skipping implementation reacts to.
+//
+// Timing is noisy, so they are ignored by default. Run them on a quiet machine with
+//   cargo test --release --test constant_time -- --ignored --test-threads=1
+use std::hint::black_box;
+use std::time::Instant;
+
+use num_bigint::BigUint;
+use rand::Rng;
+
+use zkp::{Group, ParameterSet, RistrettoGroup, ZKP};
+
+const SAMPLES: usize = 20_000;
+const THRESHOLD: f64 = 4.5;
+// measurements above this percentile are dropped, they are mostly interrupts
+const CROP_PERCENTILE: f64 = 0.9;
+
+// Welch's t statistic between the timings of the fixed and the random class
+fn t_statistic<S: Clone>(fixed: &S, random: impl Fn() -> S, operation: impl Fn(&S)) -> f64 {
+    let mut rng = rand::thread_rng();
+    let mut timings: Vec<(bool, u128)> = Vec::with_capacity(SAMPLES);
+    for _ in 0..SAMPLES {
+        let class = rng.gen::<bool>();
+        let secret = if class { random() } else { fixed.clone() };
+        let start = Instant::now();
+        operation(black_box(&secret));
+        timings.push((class, start.elapsed().as_nanos()));
+    }
+
+    let mut sorted: Vec<u128> = timings.iter().map(|(_, t)| *t).collect();
+    sorted.sort_unstable();
+    let cutoff = sorted[(sorted.len() as f64 * CROP_PERCENTILE) as usize];
+
+    let stats = |class: bool| {
+        let values: Vec<f64> = timings
+            .iter()
+            .filter(|(c, t)| *c == class && *t <= cutoff)
+            .map(|(_, t)| *t as f64)
+            .collect();
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        (mean, variance, n)
+    };
+    let (mean0, var0, n0) = stats(false);
+    let (mean1, var1, n1) = stats(true);
+    (mean0 - mean1) / (var0 / n0 + var1 / n1).sqrt()
+}
+
+// 2^(bits(q) - 1) + 1
+fn sparse_scalar(q: &BigUint) -> BigUint {
+    (BigUint::from(1u32) << (q.bits() - 1)) + 1u32
+}
+
+fn modp() -> ZKP<zkp::ModPGroup> {
+    ParameterSet::by_name("rfc5114-2048-256").unwrap().zkp().unwrap()
+}
+
+fn assert_constant_time(name: &str, t: f64) {
+    println!("{name}: t = {t:.2}");
+    assert!(t.abs() < THRESHOLD, "{name} leaks timing, t = {t:.2}");
+}
+
+#[test]
+#[ignore]
+fn test_modp_solve_is_constant_time() {
+    let zkp = modp();
+    let k = zkp.group.random_scalar();
+    let c = zkp.group.random_scalar();
+    let t = t_statistic(&sparse_scalar(&zkp.group.q), || zkp.group.random_scalar(), |x| {
+        black_box(zkp.solve(&k, &c, x));
+    });
+    assert_constant_time("mod p solve", t);
+}
+
+#[test]
+#[ignore]
+fn test_modp_statement_is_constant_time() {
+    let zkp = modp();
+    let t = t_statistic(&sparse_scalar(&zkp.group.q), || zkp.group.random_scalar(), |x| {
+        black_box(zkp.statement(x));
+    });
+    assert_constant_time("mod p statement", t);
+}
+
+#[test]
+#[ignore]
+fn test_ristretto_is_constant_time() {
+    let (alpha, beta) = RistrettoGroup::generators();
+    let zkp = ZKP::new(RistrettoGroup::new(), alpha, beta);
+    let k = zkp.group.random_scalar();
+    let c = zkp.group.random_scalar();
+    let fixed = zkp.group.hash_to_scalar(b"fixed");
+    let t = t_statistic(&fixed, || zkp.group.random_scalar(), |x| {
+        black_box(zkp.statement(x));
+        black_box(zkp.solve(&k, &c, x));
+    });
+    assert_constant_time("ristretto statement and solve", t);
+}
+
+// The harness must flag BigUint::modpow, whose running time follows the bit length of the exponent
+#[test]
+#[ignore]
+fn test_harness_detects_variable_time_modpow() {
+    let zkp = modp();
+    let t = t_statistic(&BigUint::from(3u32), || zkp.group.random_scalar(), |x| {
+        black_box(zkp.alpha.modpow(x, &zkp.group.p));
+    });
+    println!("modpow: t = {t:.2}");
+    assert!(t.abs() >= THRESHOLD, "variable time modpow was not detected, t = {t:.2}");
+}
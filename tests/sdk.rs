@@ -0,0 +1,204 @@
+// End-to-end tests of the client SDK against the `server` binary
+mod common;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tonic::Code;
+use zkp::sdk::ClientError;
+use zkp::token::PublicKey;
+use zkp::zkp_auth::{AuthenticationChallengeRequest, LoginRequest, PublicKeysRequest, RevokeSessionsRequest, RotateCredentialsRequest, SaltRequest};
+use zkp::{derive_secret, generate_salt, login_context, revoke_context, rotate_context, Group, NonceStrategy, Proof, RistrettoGroup, ZkpAuthClient};
+
+use common::{start_server, Server, KDF_PARAMS};
+
+async fn connect(server: &Server) -> ZkpAuthClient<RistrettoGroup> {
+    let url = format!("http://127.0.0.1:{}", server.port);
+    ZkpAuthClient::connect(url, common::zkp()).await.unwrap().with_kdf_params(KDF_PARAMS)
+}
+
+// A non-interactive proof for `user` over `context`, made the way the SDK makes them
+async fn prove(client: &mut ZkpAuthClient<RistrettoGroup>, user: &str, password: &str, context: &[u8]) -> Proof<RistrettoGroup> {
+    let salt = client.inner_mut().get_salt(SaltRequest { user: user.to_string() }).await.unwrap().into_inner().salt;
+    let x = derive_secret(&client.zkp().group, password.as_bytes(), &salt, &KDF_PARAMS).unwrap();
+    client.zkp().prove_noninteractive_with(user, &x, context, NonceStrategy::Hedged)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn code<T: std::fmt::Debug>(result: Result<T, ClientError>) -> Code {
+    match result {
+        Err(ClientError::Status(status)) => status.code(),
+        other => panic!("expected a status, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_sessions() {
+    let server = start_server(&[]);
+    let mut client = connect(&server).await;
+
+    client.register("alice", "secret").await.unwrap();
+    assert_eq!(code(client.register("alice", "other").await), Code::AlreadyExists);
+
+    let session = client.login("alice", "secret").await.unwrap();
+    assert_eq!(session.token, None);
+    assert_eq!(client.validate_session(&session.session_id).await.unwrap().user, "alice");
+    assert_eq!(code(client.login("alice", "wrong").await), Code::Unauthenticated);
+    assert_eq!(code(client.login("bob", "secret").await), Code::Unauthenticated);
+
+    let refreshed = client.refresh_session(&session.session_id).await.unwrap();
+    assert_eq!(refreshed.token, None);
+    assert_eq!(code(client.validate_session(&session.session_id).await), Code::Unauthenticated);
+    client.logout(&refreshed.session_id).await.unwrap();
+    assert_eq!(code(client.validate_session(&refreshed.session_id).await), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_invalid_commitment() {
+    let server = start_server(&[]);
+    let mut client = connect(&server).await;
+    client.register("alice", "secret").await.unwrap();
+
+    // malformed input is refused as such, whether the user exists or not
+    for user in ["alice", "nobody"] {
+        let request = AuthenticationChallengeRequest { user: user.to_string(), r1: vec![0; 3], r2: vec![0; 3] };
+        let err = client.inner_mut().create_authentication_challenge(request).await.unwrap_err();
+        assert_eq!((err.code(), err.message()), (Code::InvalidArgument, "Invalid r1: expected 32 bytes, got 3"));
+    }
+}
+
+#[tokio::test]
+async fn test_tokens() {
+    let der = ring::signature::Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+    let key_path = std::env::temp_dir().join(format!("zkp-token-{}.pem", std::process::id()));
+    std::fs::write(&key_path, pem::encode(&pem::Pem::new("PRIVATE KEY", der.as_ref()))).unwrap();
+    let server = start_server(&["--token-key", key_path.to_str().unwrap()]);
+    std::fs::remove_file(&key_path).unwrap();
+    let mut client = connect(&server).await;
+
+    let keys = client.inner_mut().get_public_keys(PublicKeysRequest {}).await.unwrap().into_inner().keys;
+    let key = PublicKey { kid: keys[0].kid.clone(), kty: keys[0].kty.clone(), crv: keys[0].crv.clone(), alg: keys[0].alg.clone(), x: keys[0].x.clone() };
+
+    client.register("alice", "secret").await.unwrap();
+    let session = client.login("alice", "secret").await.unwrap();
+    let claims = key.verify(session.token.as_ref().unwrap()).unwrap();
+    assert_eq!((claims.sub.as_str(), claims.exp), ("alice", session.expires_at));
+
+    // a refreshed session comes with a token that expires with it
+    let refreshed = client.refresh_session(&session.session_id).await.unwrap();
+    let claims = key.verify(refreshed.token.as_ref().unwrap()).unwrap();
+    assert_eq!((claims.sub.as_str(), claims.exp), ("alice", refreshed.expires_at));
+}
+
+#[tokio::test]
+async fn test_rotation() {
+    let server = start_server(&[]);
+    let mut client = connect(&server).await;
+
+    client.register("alice", "secret").await.unwrap();
+    client.login_noninteractive("alice", "secret").await.unwrap();
+    assert_eq!(code(client.rotate("alice", "wrong", "new").await), Code::Unauthenticated);
+
+    client.rotate("alice", "secret", "new").await.unwrap();
+    assert_eq!(code(client.login_noninteractive("alice", "secret").await), Code::Unauthenticated);
+    let session = client.login_noninteractive("alice", "new").await.unwrap();
+    assert_eq!(client.validate_session(&session.session_id).await.unwrap().user, "alice");
+    client.login("alice", "new").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_lockout() {
+    let server = start_server(&["--lockout-threshold", "2", "--lockout-secs", "60"]);
+    let mut client = connect(&server).await;
+
+    client.register("alice", "secret").await.unwrap();
+    assert_eq!(code(client.login("alice", "wrong").await), Code::Unauthenticated);
+    assert_eq!(code(client.login_noninteractive("alice", "wrong").await), Code::Unauthenticated);
+
+    // locked for a minute, even with the right password
+    let err = client.login("alice", "secret").await.unwrap_err();
+    assert!(matches!(&err, ClientError::Status(status) if status.code() == Code::ResourceExhausted), "{err}");
+    let retry_after = err.retry_after().unwrap();
+    assert!(retry_after.as_secs() > 50 && retry_after.as_secs() <= 60, "{retry_after:?}");
+    assert_eq!(code(client.login_noninteractive("alice", "secret").await), Code::ResourceExhausted);
+
+    // other users are not affected
+    client.register("bob", "secret").await.unwrap();
+    client.login("bob", "secret").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rate_limit() {
+    let server = start_server(&["--user-burst", "3", "--user-rate-per-min", "1"]);
+    let mut client = connect(&server).await;
+
+    client.register("alice", "secret").await.unwrap();
+    client.login("alice", "secret").await.unwrap();
+    client.login("alice", "secret").await.unwrap();
+    client.login("alice", "secret").await.unwrap();
+    let err = client.login("alice", "secret").await.unwrap_err();
+    assert_eq!(err.retry_after().map(|retry_after| retry_after.as_secs() > 50), Some(true), "{err}");
+
+    // other users have their own bucket
+    client.register("bob", "secret").await.unwrap();
+    client.login("bob", "secret").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_replay() {
+    // a replay that counted as a failed proof would lock the account out
+    let server = start_server(&["--lockout-threshold", "1"]);
+    let mut client = connect(&server).await;
+    client.register("alice", "secret").await.unwrap();
+
+    // a captured login cannot be sent again while its timestamp is still accepted
+    let timestamp = unix_now();
+    let proof = prove(&mut client, "alice", "secret", &login_context(timestamp)).await;
+    let group = &client.zkp().group;
+    let login = LoginRequest { user: "alice".to_string(), timestamp, c: group.encode_scalar(&proof.c), s: group.encode_scalar(&proof.s) };
+    client.inner_mut().login(login.clone()).await.unwrap();
+    assert_eq!(client.inner_mut().login(login).await.unwrap_err().code(), Code::Unauthenticated);
+    let revoked = client.login_noninteractive("alice", "secret").await.unwrap();
+
+    // nor can a captured revocation, it would end the sessions opened since
+    let timestamp = unix_now();
+    let proof = prove(&mut client, "alice", "secret", &revoke_context(timestamp)).await;
+    let group = &client.zkp().group;
+    let revoke = RevokeSessionsRequest {
+        user: "alice".to_string(),
+        timestamp,
+        c: group.encode_scalar(&proof.c),
+        s: group.encode_scalar(&proof.s),
+        admin_token: String::new(),
+    };
+    assert_eq!(client.inner_mut().revoke_sessions(revoke.clone()).await.unwrap().into_inner().revoked, 2);
+    assert_eq!(code(client.validate_session(&revoked.session_id).await), Code::Unauthenticated);
+    let session = client.login("alice", "secret").await.unwrap();
+    assert_eq!(client.inner_mut().revoke_sessions(revoke).await.unwrap_err().code(), Code::Unauthenticated);
+    assert_eq!(client.validate_session(&session.session_id).await.unwrap().user, "alice");
+
+    // nor can a captured rotation
+    let new_salt = generate_salt();
+    let new_x = derive_secret(&client.zkp().group, b"secret2", &new_salt, &KDF_PARAMS).unwrap();
+    let new = client.zkp().statement(&new_x);
+    let group = &client.zkp().group;
+    let (new_y1, new_y2) = (group.encode_element(&new.y1), group.encode_element(&new.y2));
+    let timestamp = unix_now();
+    let proof = prove(&mut client, "alice", "secret", &rotate_context(timestamp, &new_y1, &new_y2, &new_salt)).await;
+    let group = &client.zkp().group;
+    let rotate = RotateCredentialsRequest {
+        user: "alice".to_string(),
+        timestamp,
+        c: group.encode_scalar(&proof.c),
+        s: group.encode_scalar(&proof.s),
+        new_y1,
+        new_y2,
+        new_salt,
+    };
+    client.inner_mut().rotate_credentials(rotate.clone()).await.unwrap();
+    let err = client.inner_mut().rotate_credentials(rotate).await.unwrap_err();
+    assert_eq!((err.code(), err.message()), (Code::Unauthenticated, "Proof was already used"));
+    client.login("alice", "secret2").await.unwrap();
+}
@@ -0,0 +1,145 @@
+// TLS and mutual TLS between the SDK and the `server` binary, with certificates
+// from a throwaway CA generated for each test
+mod common;
+
+use std::future::Future;
+use std::path::PathBuf;
+
+use rcgen::{BasicConstraints, CertificateParams, ExtendedKeyUsagePurpose, IsCa, Issuer, KeyPair};
+use zkp::sdk::{ClientError, TlsOptions};
+use zkp::ZkpAuthClient;
+
+use common::{start_server, Server, KDF_PARAMS};
+
+// A self-signed CA that signs the certificates of one test
+struct Ca {
+    cert_pem: String,
+    issuer: Issuer<'static, KeyPair>,
+}
+
+impl Ca {
+    fn new() -> Self {
+        let mut params = CertificateParams::new(Vec::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let key = KeyPair::generate().unwrap();
+        let cert_pem = params.self_signed(&key).unwrap().pem();
+        Ca { cert_pem, issuer: Issuer::new(params, key) }
+    }
+
+    // certificate and key PEM for `names` with the given purpose
+    fn sign(&self, names: &[&str], purpose: ExtendedKeyUsagePurpose) -> (String, String) {
+        let mut params = CertificateParams::new(names.iter().map(|name| name.to_string()).collect::<Vec<_>>()).unwrap();
+        params.extended_key_usages.push(purpose);
+        let key = KeyPair::generate().unwrap();
+        (params.signed_by(&key, &self.issuer).unwrap().pem(), key.serialize_pem())
+    }
+}
+
+// Directory for the PEM files the server reads, removed when the test ends
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("zkp-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    fn write(&self, name: &str, contents: &str) -> String {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// Starts a server with a certificate for localhost, requiring client certificates of `client_ca` when given
+fn start_tls_server(dir: &TempDir, ca: &Ca, client_ca: Option<&Ca>) -> Server {
+    let (cert, key) = ca.sign(&["localhost"], ExtendedKeyUsagePurpose::ServerAuth);
+    let (cert, key) = (dir.write("server.crt", &cert), dir.write("server.key", &key));
+    let mut args = vec!["--tls-cert", &cert, "--tls-key", &key];
+    let client_ca = client_ca.map(|client_ca| dir.write("client-ca.crt", &client_ca.cert_pem));
+    if let Some(client_ca) = &client_ca {
+        args.extend(["--tls-client-ca", client_ca]);
+    }
+    start_server(&args)
+}
+
+fn options(ca: &Ca, identity: Option<(String, String)>) -> TlsOptions {
+    TlsOptions {
+        ca_cert: Some(ca.cert_pem.clone().into_bytes()),
+        identity: identity.map(|(cert, key)| (cert.into_bytes(), key.into_bytes())),
+        domain: Some("localhost".to_string()),
+    }
+}
+
+// connects and registers a user, what fails depends on where the handshake is refused
+async fn register(url: String, tls: Option<&TlsOptions>, user: &str) -> Result<(), ClientError> {
+    let mut client = match tls {
+        Some(tls) => ZkpAuthClient::connect_tls(url, tls, common::zkp()).await?,
+        None => ZkpAuthClient::connect(url, common::zkp()).await?,
+    }
+    .with_kdf_params(KDF_PARAMS);
+    client.register(user, "secret").await?;
+    client.login(user, "secret").await?;
+    Ok(())
+}
+
+// Runs a test on its own runtime and thread: a debug build needs more than the 2 MiB stack of a
+// test thread for a TLS handshake with a client certificate
+fn run<F: Future<Output = ()>>(test: impl FnOnce() -> F + Send + 'static) {
+    let thread = std::thread::Builder::new().stack_size(8 << 20).spawn(|| {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(test())
+    });
+    if let Err(panic) = thread.unwrap().join() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+#[test]
+fn test_tls() {
+    run(test_tls_body)
+}
+
+async fn test_tls_body() {
+    let dir = TempDir::new("tls");
+    let ca = Ca::new();
+    let server = start_tls_server(&dir, &ca, None);
+    let url = format!("https://127.0.0.1:{}", server.port);
+
+    register(url.clone(), Some(&options(&ca, None)), "alice").await.unwrap();
+
+    // plaintext, a server certificate from an unknown CA and a name it is not valid for all fail
+    assert!(register(format!("http://127.0.0.1:{}", server.port), None, "bob").await.is_err());
+    assert!(register(url.clone(), Some(&options(&Ca::new(), None)), "bob").await.is_err());
+    let wrong_name = TlsOptions { domain: Some("example.com".to_string()), ..options(&ca, None) };
+    assert!(register(url, Some(&wrong_name), "bob").await.is_err());
+}
+
+#[test]
+fn test_mutual_tls() {
+    run(test_mutual_tls_body)
+}
+
+async fn test_mutual_tls_body() {
+    let dir = TempDir::new("mtls");
+    let ca = Ca::new();
+    let client_ca = Ca::new();
+    let server = start_tls_server(&dir, &ca, Some(&client_ca));
+    let url = format!("https://127.0.0.1:{}", server.port);
+
+    let identity = client_ca.sign(&["alice"], ExtendedKeyUsagePurpose::ClientAuth);
+    register(url.clone(), Some(&options(&ca, Some(identity))), "alice").await.unwrap();
+
+    // no client certificate, or one signed by another CA
+    assert!(register(url.clone(), Some(&options(&ca, None)), "bob").await.is_err());
+    let rogue = Ca::new().sign(&["bob"], ExtendedKeyUsagePurpose::ClientAuth);
+    assert!(register(url, Some(&options(&ca, Some(rogue))), "bob").await.is_err());
+}
+